@@ -0,0 +1,205 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::fs::{create_dir_all, read_to_string, write};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use handlebars::Context;
+use serde_json::{Map, Value};
+
+use crate::config::{Config, QuestionSpec};
+use crate::dirs::data_file;
+use crate::spec::TemplateSpec;
+
+pub fn answers_key(template_spec: &TemplateSpec, template: Option<&str>) -> String {
+    match template {
+        Some(template) => format!("{}#{}", template_spec, template),
+        None => template_spec.to_string(),
+    }
+}
+
+pub fn load_answers(key: &str) -> io::Result<Option<Map<String, Value>>> {
+    match answers_file() {
+        Some(answers_file) => load_answers_from(&answers_file, key),
+        None => Ok(None),
+    }
+}
+
+pub fn store_answers(key: &str, config: &Config, context: &Context) -> io::Result<()> {
+    match answers_file() {
+        Some(answers_file) => {
+            store_answers_into(&answers_file, key, collect_answers(config, context))
+        }
+        None => Ok(()),
+    }
+}
+
+fn answers_file() -> Option<PathBuf> {
+    data_file("answers.json")
+}
+
+fn read_answers_file(answers_file: &Path) -> io::Result<Map<String, Value>> {
+    if answers_file.is_file() {
+        Ok(serde_json::from_str(&read_to_string(answers_file)?)?)
+    } else {
+        Ok(Map::new())
+    }
+}
+
+fn load_answers_from(answers_file: &Path, key: &str) -> io::Result<Option<Map<String, Value>>> {
+    Ok(match read_answers_file(answers_file)?.remove(key) {
+        Some(Value::Object(answers)) => Some(answers),
+        _ => None,
+    })
+}
+
+fn store_answers_into(
+    answers_file: &Path,
+    key: &str,
+    answers: Map<String, Value>,
+) -> io::Result<()> {
+    let mut all_answers = read_answers_file(answers_file)?;
+    all_answers.insert(key.to_string(), Value::Object(answers));
+
+    if let Some(parent) = answers_file.parent() {
+        create_dir_all(parent)?;
+    }
+
+    write(answers_file, serde_json::to_string_pretty(&all_answers)?)
+}
+
+fn collect_answers(config: &Config, context: &Context) -> Map<String, Value> {
+    config
+        .questions
+        .iter()
+        // Secrets must never end up on disk
+        .filter(|question| !matches!(question.spec, QuestionSpec::Text { secret: true, .. }))
+        .filter_map(|question| {
+            question
+                .path
+                .names()
+                .iter()
+                .try_fold(context.data(), |value, &name| value.get(name))
+                .map(|answer| (question.path.names().join("."), answer.clone()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use crate::config::read_config;
+    use crate::context::UnsafeContext;
+    use crate::utils::ToolConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_answers_key() {
+        let spec = TemplateSpec::Remote("https://github.com/v47-io/architect-test-template.git");
+
+        assert_eq!(
+            "https://github.com/v47-io/architect-test-template.git",
+            answers_key(&spec, None)
+        );
+
+        assert_eq!(
+            "https://github.com/v47-io/architect-test-template.git#sub/template",
+            answers_key(&spec, Some("sub/template"))
+        );
+    }
+
+    #[test]
+    fn test_store_and_load_answers() -> io::Result<()> {
+        let data_dir = tempdir()?;
+        let answers_file = data_dir.path().join("nested/answers.json");
+
+        let config = read_config(
+            r#"{
+    "questions": [
+        { "name": "author.name", "type": "Text" },
+        { "name": "token", "type": "Text", "secret": true },
+        { "name": "features", "type": "Selection", "items": ["a", "b"], "multi": true }
+    ]
+}"#,
+            &ToolConfig {
+                template: None,
+                verbose: false,
+                strict: false,
+                lint: false,
+                on_existing: None,
+                on_conflict: None,
+                no_history: false,
+                no_init: false,
+                dry_run: false,
+                ignore_checks: false,
+            },
+        )?;
+
+        let context_data = json!({
+            "__template__": {},
+            "author": { "name": "Some Author" },
+            "token": "very secret",
+            "features": { "b": true }
+        });
+
+        let context = match context_data {
+            Value::Object(map) => UnsafeContext::new(map).into(),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(None, load_answers_from(&answers_file, "template")?);
+
+        store_answers_into(
+            &answers_file,
+            "template",
+            collect_answers(&config, &context),
+        )?;
+        store_answers_into(&answers_file, "other-template", Map::new())?;
+
+        let answers = load_answers_from(&answers_file, "template")?.unwrap();
+
+        assert_eq!(Some(&json!("Some Author")), answers.get("author.name"));
+        assert_eq!(Some(&json!({ "b": true })), answers.get("features"));
+        assert_eq!(None, answers.get("token"));
+
+        assert_eq!(
+            Some(Map::new()),
+            load_answers_from(&answers_file, "other-template")?
+        );
+
+        Ok(())
+    }
+}
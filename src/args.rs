@@ -32,11 +32,11 @@
 
 use std::ffi::OsString;
 
-use clap::{crate_authors, crate_version, App, Arg, ArgMatches};
+use clap::{crate_version, App, Arg, ArgMatches};
 
 use constants::{args, flags, options};
 
-use crate::utils::constants;
+use crate::utils::{constants, ConflictStrategy, OnExisting};
 
 pub fn get_matches<'app, I, T>(args: I) -> ArgMatches<'app>
 where
@@ -45,7 +45,7 @@ where
 {
     App::new("Architect")
         .version(crate_version!())
-        .author(crate_authors!(",\n"))
+        .author(env!("CARGO_PKG_AUTHORS"))
         .about("Scaffolds your projects using platform agnostic handlebars templates")
         .arg(
             Arg::with_name(args::REPOSITORY)
@@ -68,6 +68,44 @@ Example: git@github.com:some-user/his-template-repo.git"#,
                 .takes_value(true)
                 .help("The remote branch to fetch instead of the default branch"),
         )
+        .arg(
+            Arg::with_name(options::ON_EXISTING)
+                .long(options::ON_EXISTING)
+                .takes_value(true)
+                .possible_values(&OnExisting::VALUES)
+                .help("Allows a non-empty target directory, handling existing files as specified")
+                .long_help(
+                    r#"Allows a non-empty target directory, handling existing files as specified.
+
+  - skip: Existing files are left as they are
+  - overwrite: Existing files are replaced
+  - prompt: Shows the changes and asks whether to replace each existing file
+  - backup: Existing files are renamed to <name>.bak before they are replaced
+
+Files whose content wouldn't change are always left as they are. If the target
+directory already is a Git repository, no Git history is copied or initialized"#,
+                ),
+        )
+        .arg(
+            Arg::with_name(options::ON_CONFLICT)
+                .long(options::ON_CONFLICT)
+                .takes_value(true)
+                .possible_values(&ConflictStrategy::VALUES)
+                .help("Resolves multiple files being rendered to the same target as specified")
+                .long_help(
+                    r#"Resolves multiple files being rendered to the same target as specified.
+
+  - error: Nothing is written and the conflicting files are reported
+  - first-wins: The first file (sorted by path) is used
+  - last-wins: The last file (sorted by path) is used
+  - concatenate: The contents of all files are concatenated, only for text files
+  - priority: The file matching the earliest of the priority globs in
+    .architect.json is used
+
+This overrides the strategy specified in .architect.json, which defaults to
+error. Conflicts that can't be resolved are treated like error"#,
+                ),
+        )
         .arg(
             Arg::with_name(flags::DRY_RUN)
                 .long(flags::DRY_RUN)
@@ -154,6 +192,18 @@ Instead the target directory will be initialized as a new Git repository"#,
 This requires the --no-history flag to be specified as well"#,
                 ),
         )
+        .arg(
+            Arg::with_name(flags::FRESH)
+                .long(flags::FRESH)
+                .help("Ignores the answers remembered from the last time this template was used")
+                .long_help(
+                    r#"Ignores the answers remembered from the last time this template was used.
+
+Architect remembers your answers for each template (and sub-template) and offers them
+as the default values the next time you use it. With this flag you only get the
+default values specified by the template"#,
+                ),
+        )
         .arg(
             Arg::with_name(flags::IGNORE_CHECKS)
                 .long(flags::IGNORE_CHECKS)
@@ -168,6 +218,52 @@ These errors will be ignored:
   - Condition evaluation errors (for conditional files)"#,
                 ),
         )
+        .arg(
+            Arg::with_name(flags::STRICT)
+                .long(flags::STRICT)
+                .help("Fails on references to undefined values instead of rendering them empty")
+                .long_help(
+                    r#"Fails on references to undefined values instead of rendering them empty.
+
+This enables the strict mode of Handlebars for file contents, file and directory
+names as well as the Handlebars conditions of conditional files. Every undefined
+reference is reported with the file and line it occurs in.
+
+Templates can also enable this themselves using the "strict" option in
+.architect.json"#,
+                ),
+        )
+        .arg(
+            Arg::with_name(flags::LINT)
+                .long(flags::LINT)
+                .conflicts_with(flags::DRY_RUN)
+                .help("Analyzes the template instead of generating a project from it")
+                .long_help(
+                    r#"Analyzes the template instead of generating a project from it.
+
+All template files, templated file and directory names, partials, conditions and
+hook commands are parsed to find values that aren't defined by any question or
+data file, questions that are never used, and calls of helpers that don't exist.
+
+No questions are asked and no target directory is created. The exit code is 1
+if any issues were found"#,
+                ),
+        )
+        .arg(
+            Arg::with_name(flags::TRUST)
+                .long(flags::TRUST)
+                .help("Trusts the template to run its commands without asking")
+                .long_help(
+                    r#"Trusts the template to run its commands without asking.
+
+Templates can run commands as hooks and helpers. Before they do, Architect lists
+these commands and asks whether the template is trusted. Confirmed template
+sources are remembered together with their commit.
+
+In non-interactive mode, e.g. on CI, templates with commands are refused unless
+they were trusted before or this flag is given"#,
+                ),
+        )
         .arg(
             Arg::with_name(flags::VERBOSE)
                 .long(flags::VERBOSE)
@@ -0,0 +1,737 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::fmt;
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::CharIndices;
+
+use serde_json::Value;
+
+/// A boolean expression that is evaluated directly on the JSON context.
+///
+/// Supports `&&`, `||`, `!`, `==`, `!=`, `in` and `contains`, parentheses, dotted paths
+/// into the context, and string, number, boolean, `null` and list literals
+#[derive(Debug, PartialEq)]
+pub struct Expression {
+    root: Expr,
+}
+
+#[derive(Debug, PartialEq)]
+enum Expr {
+    Literal(Value),
+    Path(Vec<String>),
+    List(Vec<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Eq(Box<Expr>, Box<Expr>),
+    NotEq(Box<Expr>, Box<Expr>),
+    In(Box<Expr>, Box<Expr>),
+    Contains(Box<Expr>, Box<Expr>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConditionError {
+    Syntax { column: usize, message: String },
+    UnknownValue(String),
+    Type(String),
+}
+
+impl Display for ConditionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConditionError::Syntax { column, message } => {
+                write!(f, "Syntax error at column {}: {}", column, message)
+            }
+            ConditionError::UnknownValue(path) => {
+                write!(f, "'{}' doesn't exist in the context", path)
+            }
+            ConditionError::Type(message) => write!(f, "Type error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for ConditionError {}
+
+impl Expression {
+    pub fn parse(source: &str) -> Result<Expression, ConditionError> {
+        let tokens = tokenize(source)?;
+        let mut parser = Parser {
+            tokens,
+            pos: 0,
+            end: source.chars().count() + 1,
+        };
+
+        let root = parser.parse_or()?;
+
+        if let Some((column, token)) = parser.tokens.get(parser.pos) {
+            return Err(ConditionError::Syntax {
+                column: *column,
+                message: format!("Unexpected {}", token),
+            });
+        }
+
+        Ok(Expression { root })
+    }
+
+    /// Evaluates the expression, a `null` result counts as `false`
+    pub fn eval(&self, context: &Value) -> Result<bool, ConditionError> {
+        let result = eval(&self.root, context)?;
+        as_bool(&result, &self.root, "condition")
+    }
+
+    /// All context paths referenced by the expression, e.g. `nested.name`
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = vec![];
+        collect_paths(&self.root, &mut paths);
+        paths
+    }
+}
+
+fn collect_paths(expr: &Expr, paths: &mut Vec<String>) {
+    match expr {
+        Expr::Literal(_) => (),
+        Expr::Path(path) => paths.push(path.join(".")),
+        Expr::List(items) => items.iter().for_each(|it| collect_paths(it, paths)),
+        Expr::Not(operand) => collect_paths(operand, paths),
+        Expr::And(left, right)
+        | Expr::Or(left, right)
+        | Expr::Eq(left, right)
+        | Expr::NotEq(left, right)
+        | Expr::In(left, right)
+        | Expr::Contains(left, right) => {
+            collect_paths(left, paths);
+            collect_paths(right, paths);
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    LParen,
+    RParen,
+    LBracket,
+    RBracket,
+    Comma,
+    Not,
+    And,
+    Or,
+    Eq,
+    NotEq,
+    In,
+    Contains,
+    Literal(Value),
+    Path(Vec<String>),
+}
+
+impl Display for Token {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Token::LParen => f.write_str("'('"),
+            Token::RParen => f.write_str("')'"),
+            Token::LBracket => f.write_str("'['"),
+            Token::RBracket => f.write_str("']'"),
+            Token::Comma => f.write_str("','"),
+            Token::Not => f.write_str("'!'"),
+            Token::And => f.write_str("'&&'"),
+            Token::Or => f.write_str("'||'"),
+            Token::Eq => f.write_str("'=='"),
+            Token::NotEq => f.write_str("'!='"),
+            Token::In => f.write_str("'in'"),
+            Token::Contains => f.write_str("'contains'"),
+            Token::Literal(value) => write!(f, "'{}'", value),
+            Token::Path(path) => write!(f, "'{}'", path.join(".")),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, ConditionError> {
+    let mut tokens = vec![];
+    // column numbers are 1-based and count chars, not bytes
+    let mut chars = source.char_indices().peekable();
+    let column_of = |byte_index: usize| source[..byte_index].chars().count() + 1;
+
+    while let Some(&(start, c)) = chars.peek() {
+        let column = column_of(start);
+
+        let token = match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+                continue;
+            }
+            '(' | ')' | '[' | ']' | ',' => {
+                chars.next();
+                match c {
+                    '(' => Token::LParen,
+                    ')' => Token::RParen,
+                    '[' => Token::LBracket,
+                    ']' => Token::RBracket,
+                    _ => Token::Comma,
+                }
+            }
+            '!' | '=' | '&' | '|' => {
+                chars.next();
+                let next = chars.peek().map(|&(_, next)| next);
+                match (c, next) {
+                    ('!', Some('=')) => {
+                        chars.next();
+                        Token::NotEq
+                    }
+                    ('!', _) => Token::Not,
+                    ('=', Some('=')) | ('&', Some('&')) | ('|', Some('|')) => {
+                        chars.next();
+                        match c {
+                            '=' => Token::Eq,
+                            '&' => Token::And,
+                            _ => Token::Or,
+                        }
+                    }
+                    _ => {
+                        return Err(ConditionError::Syntax {
+                            column,
+                            message: format!(
+                                "Unknown operator '{}', did you mean '{}{}'?",
+                                c, c, c
+                            ),
+                        })
+                    }
+                }
+            }
+            '"' | '\'' => Token::Literal(Value::String(read_string(&mut chars, column)?)),
+            _ if c == '-' || c.is_ascii_digit() => {
+                let text = read_while(source, &mut chars, |it| {
+                    it == '-' || it == '.' || it.is_ascii_digit()
+                });
+                match serde_json::from_str::<serde_json::Number>(text) {
+                    Ok(number) => Token::Literal(Value::Number(number)),
+                    Err(_) => {
+                        return Err(ConditionError::Syntax {
+                            column,
+                            message: format!("Invalid number '{}'", text),
+                        })
+                    }
+                }
+            }
+            _ if c.is_ascii_alphabetic() || c == '_' || c == '$' => {
+                let text = read_while(source, &mut chars, |it| {
+                    it.is_ascii_alphanumeric() || it == '_' || it == '$' || it == '.'
+                });
+                match text {
+                    "true" => Token::Literal(Value::Bool(true)),
+                    "false" => Token::Literal(Value::Bool(false)),
+                    "null" => Token::Literal(Value::Null),
+                    "in" => Token::In,
+                    "contains" => Token::Contains,
+                    _ => {
+                        let path = text.split('.').map(String::from).collect::<Vec<_>>();
+                        if path.iter().any(|it| it.is_empty()) {
+                            return Err(ConditionError::Syntax {
+                                column,
+                                message: format!("Invalid path '{}'", text),
+                            });
+                        }
+                        Token::Path(path)
+                    }
+                }
+            }
+            _ => {
+                return Err(ConditionError::Syntax {
+                    column,
+                    message: format!("Unexpected character '{}'", c),
+                })
+            }
+        };
+
+        tokens.push((column, token));
+    }
+
+    Ok(tokens)
+}
+
+fn read_while<'a>(
+    source: &'a str,
+    chars: &mut Peekable<CharIndices>,
+    predicate: impl Fn(char) -> bool,
+) -> &'a str {
+    let start = chars.peek().map(|&(i, _)| i).unwrap_or(source.len());
+    let mut end = start;
+
+    while let Some(&(i, c)) = chars.peek() {
+        if !predicate(c) {
+            break;
+        }
+
+        end = i + c.len_utf8();
+        chars.next();
+    }
+
+    &source[start..end]
+}
+
+fn read_string(chars: &mut Peekable<CharIndices>, column: usize) -> Result<String, ConditionError> {
+    let (_, quote) = chars.next().unwrap();
+    let mut result = String::new();
+
+    while let Some((_, c)) = chars.next() {
+        match c {
+            _ if c == quote => return Ok(result),
+            '\\' => match chars.next() {
+                Some((_, 'n')) => result.push('\n'),
+                Some((_, 't')) => result.push('\t'),
+                Some((_, escaped)) => result.push(escaped),
+                None => break,
+            },
+            _ => result.push(c),
+        }
+    }
+
+    Err(ConditionError::Syntax {
+        column,
+        message: "Unterminated string".into(),
+    })
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .map(|&(column, _)| column)
+            .unwrap_or(self.end)
+    }
+
+    fn next_if(&mut self, expected: &Token) -> bool {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> ConditionError {
+        ConditionError::Syntax {
+            column: self.column(),
+            message: match self.peek() {
+                Some(token) => format!("Expected {}, found {}", expected, token),
+                None => format!("Expected {}, found the end of the condition", expected),
+            },
+        }
+    }
+
+    fn parse_or(&mut self) -> Result<Expr, ConditionError> {
+        let mut left = self.parse_and()?;
+
+        while self.next_if(&Token::Or) {
+            left = Expr::Or(Box::new(left), Box::new(self.parse_and()?));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_and(&mut self) -> Result<Expr, ConditionError> {
+        let mut left = self.parse_not()?;
+
+        while self.next_if(&Token::And) {
+            left = Expr::And(Box::new(left), Box::new(self.parse_not()?));
+        }
+
+        Ok(left)
+    }
+
+    fn parse_not(&mut self) -> Result<Expr, ConditionError> {
+        if self.next_if(&Token::Not) {
+            Ok(Expr::Not(Box::new(self.parse_not()?)))
+        } else {
+            self.parse_comparison()
+        }
+    }
+
+    fn parse_comparison(&mut self) -> Result<Expr, ConditionError> {
+        let left = self.parse_primary()?;
+
+        let constructor: fn(Box<Expr>, Box<Expr>) -> Expr = match self.peek() {
+            Some(Token::Eq) => Expr::Eq,
+            Some(Token::NotEq) => Expr::NotEq,
+            Some(Token::In) => Expr::In,
+            Some(Token::Contains) => Expr::Contains,
+            _ => return Ok(left),
+        };
+
+        self.pos += 1;
+        let right = self.parse_primary()?;
+
+        Ok(constructor(Box::new(left), Box::new(right)))
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ConditionError> {
+        let expr = match self.tokens.get(self.pos) {
+            Some((_, Token::LParen)) => {
+                self.pos += 1;
+                let expr = self.parse_or()?;
+                if !self.next_if(&Token::RParen) {
+                    return Err(self.unexpected("')'"));
+                }
+                return Ok(expr);
+            }
+            Some((_, Token::LBracket)) => {
+                self.pos += 1;
+                let mut items = vec![];
+                if !self.next_if(&Token::RBracket) {
+                    loop {
+                        items.push(self.parse_primary()?);
+                        if self.next_if(&Token::RBracket) {
+                            break;
+                        }
+                        if !self.next_if(&Token::Comma) {
+                            return Err(self.unexpected("',' or ']'"));
+                        }
+                    }
+                }
+                return Ok(Expr::List(items));
+            }
+            Some((_, Token::Literal(value))) => Expr::Literal(value.clone()),
+            Some((_, Token::Path(path))) => Expr::Path(path.clone()),
+            _ => return Err(self.unexpected("a value")),
+        };
+
+        self.pos += 1;
+        Ok(expr)
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Literal(value) => write!(f, "{}", value),
+            Expr::Path(path) => f.write_str(&path.join(".")),
+            Expr::List(items) => {
+                f.write_str("[")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                f.write_str("]")
+            }
+            Expr::Not(expr) => write!(f, "!{}", expr),
+            Expr::And(left, right) => write!(f, "({} && {})", left, right),
+            Expr::Or(left, right) => write!(f, "({} || {})", left, right),
+            Expr::Eq(left, right) => write!(f, "{} == {}", left, right),
+            Expr::NotEq(left, right) => write!(f, "{} != {}", left, right),
+            Expr::In(left, right) => write!(f, "{} in {}", left, right),
+            Expr::Contains(left, right) => write!(f, "{} contains {}", left, right),
+        }
+    }
+}
+
+fn eval(expr: &Expr, context: &Value) -> Result<Value, ConditionError> {
+    Ok(match expr {
+        Expr::Literal(value) => value.clone(),
+        Expr::Path(path) => lookup(path, context)?,
+        Expr::List(items) => Value::Array(
+            items
+                .iter()
+                .map(|it| eval(it, context))
+                .collect::<Result<_, _>>()?,
+        ),
+        Expr::Not(inner) => Value::Bool(!as_bool(&eval(inner, context)?, inner, "!")?),
+        Expr::And(left, right) => Value::Bool(
+            as_bool(&eval(left, context)?, left, "&&")?
+                && as_bool(&eval(right, context)?, right, "&&")?,
+        ),
+        Expr::Or(left, right) => Value::Bool(
+            as_bool(&eval(left, context)?, left, "||")?
+                || as_bool(&eval(right, context)?, right, "||")?,
+        ),
+        Expr::Eq(left, right) => Value::Bool(equals(&eval(left, context)?, &eval(right, context)?)),
+        Expr::NotEq(left, right) => {
+            Value::Bool(!equals(&eval(left, context)?, &eval(right, context)?))
+        }
+        Expr::In(needle, haystack) => Value::Bool(contains(
+            &eval(haystack, context)?,
+            haystack,
+            &eval(needle, context)?,
+            "in",
+        )?),
+        Expr::Contains(haystack, needle) => Value::Bool(contains(
+            &eval(haystack, context)?,
+            haystack,
+            &eval(needle, context)?,
+            "contains",
+        )?),
+    })
+}
+
+/// The first name of a path must exist in the context, missing properties below it are `null`
+fn lookup(path: &[String], context: &Value) -> Result<Value, ConditionError> {
+    let mut current = match context.get(&path[0]) {
+        Some(value) => value,
+        None => return Err(ConditionError::UnknownValue(path[0].clone())),
+    };
+
+    for (i, name) in path.iter().enumerate().skip(1) {
+        current = match current {
+            Value::Object(map) => match map.get(name) {
+                Some(value) => value,
+                None => return Ok(Value::Null),
+            },
+            Value::Null => return Ok(Value::Null),
+            _ => {
+                return Err(ConditionError::Type(format!(
+                    "'{}' is {}, not an object",
+                    path[..i].join("."),
+                    type_name(current)
+                )))
+            }
+        };
+    }
+
+    Ok(current.clone())
+}
+
+fn as_bool(value: &Value, expr: &Expr, operator: &str) -> Result<bool, ConditionError> {
+    match value {
+        Value::Bool(value) => Ok(*value),
+        Value::Null => Ok(false),
+        _ => Err(ConditionError::Type(format!(
+            "{} expects a boolean, but '{}' is {}",
+            describe_operator(operator),
+            expr,
+            type_name(value)
+        ))),
+    }
+}
+
+fn equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => left.as_f64() == right.as_f64(),
+        _ => left == right,
+    }
+}
+
+fn contains(
+    haystack: &Value,
+    haystack_expr: &Expr,
+    needle: &Value,
+    operator: &str,
+) -> Result<bool, ConditionError> {
+    match (haystack, needle) {
+        (Value::Array(items), _) => Ok(items.iter().any(|it| equals(it, needle))),
+        // multi-selections are objects with a `true` property for every selected item
+        (Value::Object(map), Value::String(key)) => Ok(map.get(key) == Some(&Value::Bool(true))),
+        (Value::String(text), Value::String(part)) => Ok(text.contains(part.as_str())),
+        (Value::Null, _) => Ok(false),
+        (Value::Object(_), _) | (Value::String(_), _) => Err(ConditionError::Type(format!(
+            "{} can only look for a string in '{}' which is {}, but got {}",
+            describe_operator(operator),
+            haystack_expr,
+            type_name(haystack),
+            type_name(needle)
+        ))),
+        _ => Err(ConditionError::Type(format!(
+            "{} expects a list, selection or string, but '{}' is {}",
+            describe_operator(operator),
+            haystack_expr,
+            type_name(haystack)
+        ))),
+    }
+}
+
+fn describe_operator(operator: &str) -> String {
+    if operator == "condition" {
+        "The condition".into()
+    } else {
+        format!("Operator '{}'", operator)
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "a boolean",
+        Value::Number(_) => "a number",
+        Value::String(_) => "a string",
+        Value::Array(_) => "a list",
+        Value::Object(_) => "an object",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn eval_str(source: &str, context: &Value) -> Result<bool, ConditionError> {
+        Expression::parse(source)?.eval(context)
+    }
+
+    #[test]
+    fn test_eval() {
+        let context = json!({
+            "useDocker": true,
+            "useKafka": false,
+            "count": 0,
+            "language": "kotlin",
+            "features": { "logging": true, "metrics": true },
+            "tags": ["web", "api"],
+            "nested": { "name": "value" }
+        });
+
+        assert_eq!(Ok(true), eval_str("useDocker", &context));
+        assert_eq!(Ok(false), eval_str("!useDocker", &context));
+        assert_eq!(Ok(true), eval_str("useDocker && !useKafka", &context));
+        assert_eq!(Ok(true), eval_str("useKafka || useDocker", &context));
+        assert_eq!(Ok(true), eval_str("count == 0", &context));
+        assert_eq!(Ok(true), eval_str("count == 0.0", &context));
+        assert_eq!(Ok(true), eval_str("language == 'kotlin'", &context));
+        assert_eq!(Ok(true), eval_str("language != \"java\"", &context));
+        assert_eq!(
+            Ok(true),
+            eval_str("language in ['java', 'kotlin']", &context)
+        );
+        assert_eq!(Ok(true), eval_str("features contains 'logging'", &context));
+        assert_eq!(Ok(false), eval_str("features contains 'tracing'", &context));
+        assert_eq!(Ok(true), eval_str("'metrics' in features", &context));
+        assert_eq!(Ok(true), eval_str("!features contains 'tracing'", &context));
+        assert_eq!(Ok(true), eval_str("tags contains 'api'", &context));
+        assert_eq!(Ok(true), eval_str("language contains 'lin'", &context));
+        assert_eq!(Ok(true), eval_str("nested.name == 'value'", &context));
+        assert_eq!(Ok(false), eval_str("nested.missing", &context));
+        assert_eq!(Ok(false), eval_str("features.tracing", &context));
+        assert_eq!(
+            Ok(true),
+            eval_str(
+                "(useKafka || features.logging) && !(language == 'java')",
+                &context
+            )
+        );
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        let context = json!({ "count": 0, "language": "kotlin", "features": {} });
+
+        assert_eq!(
+            Err(ConditionError::UnknownValue("missing".into())),
+            eval_str("missing", &context)
+        );
+        assert_eq!(
+            Err(ConditionError::Type(
+                "The condition expects a boolean, but 'count' is a number".into()
+            )),
+            eval_str("count", &context)
+        );
+        assert_eq!(
+            Err(ConditionError::Type(
+                "Operator '&&' expects a boolean, but 'language' is a string".into()
+            )),
+            eval_str("true && language", &context)
+        );
+        assert_eq!(
+            Err(ConditionError::Type(
+                "Operator 'contains' expects a list, selection or string, but 'count' is a number"
+                    .into()
+            )),
+            eval_str("count contains 1", &context)
+        );
+        assert_eq!(
+            Err(ConditionError::Type(
+                "'language' is a string, not an object".into()
+            )),
+            eval_str("language.name", &context)
+        );
+    }
+
+    #[test]
+    fn test_paths() {
+        let expression =
+            Expression::parse("useDocker && (language in ['java', other] || !nested.name)")
+                .unwrap();
+
+        assert_eq!(
+            vec!["useDocker", "language", "other", "nested.name"],
+            expression.paths()
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            Err(ConditionError::Syntax {
+                column: 3,
+                message: "Unknown operator '&', did you mean '&&'?".into()
+            }),
+            Expression::parse("a & b")
+        );
+        assert_eq!(
+            Err(ConditionError::Syntax {
+                column: 6,
+                message: "Expected a value, found the end of the condition".into()
+            }),
+            Expression::parse("a && ")
+        );
+        assert_eq!(
+            Err(ConditionError::Syntax {
+                column: 8,
+                message: "Unexpected '=='".into()
+            }),
+            Expression::parse("a == b == c")
+        );
+        assert_eq!(
+            Err(ConditionError::Syntax {
+                column: 6,
+                message: "Unterminated string".into()
+            }),
+            Expression::parse("a == 'b")
+        );
+        assert_eq!(
+            Err(ConditionError::Syntax {
+                column: 3,
+                message: "Expected ')', found the end of the condition".into()
+            }),
+            Expression::parse("(a")
+        );
+    }
+}
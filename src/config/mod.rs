@@ -30,19 +30,31 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::env::var;
+use std::fmt;
+use std::fmt::{Display, Formatter};
 use std::fs::{metadata, read_to_string};
 use std::io;
 use std::io::{Error, ErrorKind};
-use std::path::Path;
+use std::path::{Component, Path};
+use std::time::Duration;
 
 use crossterm::style::Stylize;
+use encoding_rs::Encoding;
 use globset::GlobMatcher;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
-use serde_json::Value;
+use serde_json::{Map, Value};
 
-use crate::utils::{glob, is_identifier, ToolConfig, ID_REGEX};
+use crate::condition::Expression;
+use crate::fetch::read_git_config_value;
+use crate::helpers::EscapeMode;
+use crate::utils::{glob, is_identifier, parse_mode, ConflictStrategy, ToolConfig, ID_REGEX};
+
+pub const DEFAULT_PARTIALS_DIR: &str = ".architect/partials";
+
+const DEFAULT_HELPER_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub fn load_config_file(root_dir: &Path, base_path: &Path) -> io::Result<Option<String>> {
     let config_file_path = base_path.join(".architect.json");
@@ -64,41 +76,50 @@ pub fn read_config<'cfg>(input: &'cfg str, tool_config: &ToolConfig) -> io::Resu
 
     let mut context_tree = HashMap::new();
 
-    let questions = json
-        .questions
+    let data = json
+        .data
         .unwrap_or_default()
         .iter()
-        .filter_map(|raw_question| {
-            let path = match QuestionPath::parse(raw_question.name) {
-                Some(path) => path,
-                None => {
-                    eprintln!(
-                        "{}: It doesn't match the format => dot-delimited {}",
-                        format!("'{}' is an invalid question name", raw_question.name).red(),
-                        ID_REGEX.as_str().bold()
-                    );
-
-                    return None;
-                }
-            };
-
-            if *path.names().first().unwrap() == "__template__" {
+        .filter_map(|raw_data_file| {
+            let path = parse_context_path(raw_data_file.name, "data", &mut context_tree)?;
+
+            let file = raw_data_file.file.trim();
+            let file_name_lower = file.to_lowercase();
+
+            let format = if file_name_lower.ends_with(".json") {
+                DataFormat::Json
+            } else if file_name_lower.ends_with(".yaml") || file_name_lower.ends_with(".yml") {
+                DataFormat::Yaml
+            } else if file_name_lower.ends_with(".toml") {
+                DataFormat::Toml
+            } else {
                 eprintln!(
-                    "{}: '{}' is a reserved name",
-                    format!("'{}' is an invalid question name", raw_question.name).red(),
-                    "__template__".bold()
+                    "{}: Unsupported file type (expected .json, .yaml, .yml, or .toml): {}",
+                    format!("Data file '{}' has an issue", raw_data_file.name).red(),
+                    file
                 );
 
                 return None;
-            }
+            };
+
+            Some(DataFile { path, file, format })
+        })
+        .collect();
 
-            if !check_context_tree(&mut context_tree, path.names()) {
+    let questions = json
+        .questions
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|raw_question| {
+            let path = parse_context_path(raw_question.name, "question", &mut context_tree)?;
+
+            let default_from_provider = matches!(raw_question.default, Some(Value::Object(_)));
+
+            if raw_question.secret.unwrap_or(false) && !matches!(raw_question.question_type, RawQuestionType::Text) {
                 eprintln!(
-                    "{}: Some of its parts refer to a value, not an object",
-                    format!("'{}' is an invalid question name", raw_question.name).red()
+                    "{}: Only questions of type 'Text' can be secret",
+                    format!("Question '{}' has an issue", raw_question.name).dark_yellow()
                 );
-
-                return None;
             }
 
             let default_value = match read_default_value(
@@ -149,6 +170,7 @@ pub fn read_config<'cfg>(input: &'cfg str, tool_config: &ToolConfig) -> io::Resu
                     },
                     RawQuestionType::Text => QuestionSpec::Text {
                         default: get_default_str(default_value),
+                        secret: raw_question.secret.unwrap_or(false),
                     },
                     RawQuestionType::Selection => {
                         let items = if let Some(raw_items) = &raw_question.items {
@@ -172,8 +194,10 @@ pub fn read_config<'cfg>(input: &'cfg str, tool_config: &ToolConfig) -> io::Resu
 
                         let default = get_default_str_list(default_value);
                         let mut default = if default.iter().any(|item| !items.contains(&&**item)) {
+                            let lenient = tool_config.ignore_checks || default_from_provider;
+
                             let mut styled_message = format!("Question '{}' has an issue", raw_question.name).stylize();
-                            styled_message = if tool_config.ignore_checks {
+                            styled_message = if lenient {
                                 styled_message.dark_yellow()
                             } else {
                                 styled_message.red()
@@ -184,7 +208,7 @@ pub fn read_config<'cfg>(input: &'cfg str, tool_config: &ToolConfig) -> io::Resu
                                 styled_message
                             );
 
-                            if tool_config.ignore_checks {
+                            if lenient {
                                 vec![]
                             } else {
                                 return None;
@@ -238,22 +262,26 @@ pub fn read_config<'cfg>(input: &'cfg str, tool_config: &ToolConfig) -> io::Resu
                             }
                         };
 
-                        let default = get_default_str(default_value);
+                        let mut default = get_default_str(default_value);
+
+                        if let Some(value) = default.as_ref().filter(|&it| !regex.is_match(it)) {
+                            let lenient = tool_config.ignore_checks || default_from_provider;
+
+                            let mut styled_message = format!("Question '{}' has an issue", raw_question.name).stylize();
+                            styled_message = if lenient {
+                                styled_message.dark_yellow()
+                            } else {
+                                styled_message.red()
+                            };
 
-                        if let Some(default) = &default {
-                            if !regex.is_match(default) {
-                                let mut styled_message = format!("Question '{}' has an issue", raw_question.name).stylize();
-                                styled_message = if tool_config.ignore_checks {
-                                    styled_message.dark_yellow()
-                                } else {
-                                    styled_message.red()
-                                };
+                            eprintln!("{}: The default value doesn't match the format '{}': {}", styled_message, format, value);
 
-                                eprintln!("{}: The default value doesn't match the format '{}': {}", styled_message, format, default);
+                            if !lenient {
+                                return None;
+                            }
 
-                                if !tool_config.ignore_checks {
-                                    return None;
-                                }
+                            if default_from_provider {
+                                default = None;
                             }
                         }
 
@@ -270,6 +298,16 @@ pub fn read_config<'cfg>(input: &'cfg str, tool_config: &ToolConfig) -> io::Resu
     Ok(Config {
         name: json.name.map(|it| it.trim()),
         version: json.version.map(|it| it.trim()),
+        data,
+        partials_dir: read_partials_dir(json.partials_dir),
+        helpers: read_helpers(&json.helpers.unwrap_or_default()),
+        escape_modes: read_escape_modes(&json.escape_modes.unwrap_or_default()),
+        strict: json.strict.unwrap_or(false),
+        conflicts: read_conflicts(json.conflicts),
+        hooks: read_hooks(json.hooks)?,
+        modes: read_modes(&json.modes.unwrap_or_default()),
+        encodings: read_encodings(&json.encodings.unwrap_or_default()),
+        delimiters: read_delimiters(&json.delimiters.unwrap_or_default()),
         questions,
         filters: json
             .filters
@@ -278,41 +316,457 @@ pub fn read_config<'cfg>(input: &'cfg str, tool_config: &ToolConfig) -> io::Resu
     })
 }
 
+fn parse_context_path<'cfg>(
+    name: &'cfg str,
+    kind: &str,
+    context_tree: &mut HashMap<String, ValueMapItem>,
+) -> Option<QuestionPath<'cfg>> {
+    let path = match QuestionPath::parse(name) {
+        Some(path) => path,
+        None => {
+            eprintln!(
+                "{}: It doesn't match the format => dot-delimited {}",
+                format!("'{}' is an invalid {} name", name, kind).red(),
+                ID_REGEX.as_str().bold()
+            );
+
+            return None;
+        }
+    };
+
+    if *path.names().first().unwrap() == "__template__" {
+        eprintln!(
+            "{}: '{}' is a reserved name",
+            format!("'{}' is an invalid {} name", name, kind).red(),
+            "__template__".bold()
+        );
+
+        return None;
+    }
+
+    if !check_context_tree(context_tree, path.names()) {
+        eprintln!(
+            "{}: Some of its parts refer to a value, not an object",
+            format!("'{}' is an invalid {} name", name, kind).red()
+        );
+
+        return None;
+    }
+
+    Some(path)
+}
+
+fn is_inside_template(path: &str) -> bool {
+    !path.is_empty()
+        && !Path::new(path).is_absolute()
+        && Path::new(path)
+            .components()
+            .all(|it| it != Component::ParentDir)
+}
+
+fn read_partials_dir(raw_partials_dir: Option<&str>) -> &str {
+    match raw_partials_dir.map(|it| it.trim()) {
+        Some(dir) if !is_inside_template(dir) => {
+            eprintln!(
+                "{}: It must be a relative path inside the template, using '{}' instead",
+                format!("Partials directory '{}' has an issue", dir).red(),
+                DEFAULT_PARTIALS_DIR
+            );
+
+            DEFAULT_PARTIALS_DIR
+        }
+        Some(dir) => dir,
+        None => DEFAULT_PARTIALS_DIR,
+    }
+}
+
+fn read_helpers<'cfg>(raw_helpers: &[RawHelper<'cfg>]) -> Vec<HelperSpec<'cfg>> {
+    let mut names = HashSet::new();
+
+    raw_helpers
+        .iter()
+        .filter_map(|raw_helper| {
+            let name = raw_helper.name.trim();
+            let report_issue = |issue: &str| {
+                eprintln!(
+                    "{}: {}",
+                    format!("Helper '{}' has an issue", name).red(),
+                    issue
+                )
+            };
+
+            if !is_identifier(name) {
+                report_issue(&format!(
+                    "Its name doesn't match the format => {}",
+                    ID_REGEX.as_str().bold()
+                ));
+                return None;
+            }
+
+            if !names.insert(name) {
+                report_issue("Its name is used by another helper");
+                return None;
+            }
+
+            let source = match (raw_helper.script, &raw_helper.command) {
+                (Some(script), None) => {
+                    let script = script.trim();
+                    if !is_inside_template(script) {
+                        report_issue("The script must be a relative path inside the template");
+                        return None;
+                    }
+
+                    HelperSource::Script(script)
+                }
+                (None, Some(command)) => {
+                    let program = match command.first() {
+                        Some(program) if !program.trim().is_empty() => program.trim(),
+                        _ => {
+                            report_issue("The command must not be empty");
+                            return None;
+                        }
+                    };
+
+                    // Programs specified as a path are resolved relative to the template
+                    if Path::new(program).components().count() > 1 && !is_inside_template(program)
+                    {
+                        report_issue("The program must be a relative path inside the template or a name looked up in PATH");
+                        return None;
+                    }
+
+                    let timeout = match raw_helper.timeout {
+                        Some(0) => {
+                            report_issue("The timeout must be greater than 0");
+                            return None;
+                        }
+                        Some(timeout) => Duration::from_millis(timeout),
+                        None => DEFAULT_HELPER_TIMEOUT,
+                    };
+
+                    HelperSource::Command {
+                        command: std::iter::once(program)
+                            .chain(command[1..].iter().copied())
+                            .collect(),
+                        timeout,
+                    }
+                }
+                _ => {
+                    report_issue("Exactly one of script or command must be specified");
+                    return None;
+                }
+            };
+
+            Some(HelperSpec { name, source })
+        })
+        .collect()
+}
+
+fn read_escape_modes(raw_escape_modes: &HashMap<&str, &str>) -> HashMap<String, EscapeMode> {
+    raw_escape_modes
+        .iter()
+        .filter_map(|(&extension, &mode)| {
+            let extension = extension.trim().trim_start_matches('.').to_lowercase();
+
+            match EscapeMode::parse(mode) {
+                Some(mode) if !extension.is_empty() => Some((extension, mode)),
+                Some(_) => {
+                    eprintln!(
+                        "{}: The extension is blank",
+                        "Escape mode has an issue".red()
+                    );
+                    None
+                }
+                None => {
+                    eprintln!(
+                        "{}: Unknown escape mode '{}' (expected html, json, yaml, toml, xml, shell, or none)",
+                        format!("Escape mode for '{}' has an issue", extension).red(),
+                        mode
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn read_modes(raw_modes: &[RawFileMode]) -> Vec<FileMode> {
+    raw_modes
+        .iter()
+        .filter_map(|raw_mode| {
+            let matcher = match glob(raw_mode.matcher) {
+                Ok(matcher) => matcher,
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "Failed to parse glob expression {} ({}) in modes",
+                            raw_mode.matcher, e
+                        )
+                        .red()
+                    );
+                    return None;
+                }
+            };
+
+            match parse_mode(raw_mode.mode) {
+                Some(mode) => Some(FileMode { matcher, mode }),
+                None => {
+                    eprintln!(
+                        "{}: Invalid mode '{}' (expected an octal number like 755)",
+                        format!("Mode for '{}' has an issue", raw_mode.matcher).red(),
+                        raw_mode.mode
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn read_encodings(raw_encodings: &[RawFileEncoding]) -> Vec<FileEncoding> {
+    raw_encodings
+        .iter()
+        .filter_map(|raw_encoding| {
+            let matcher = match glob(raw_encoding.matcher) {
+                Ok(matcher) => matcher,
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "Failed to parse glob expression {} ({}) in encodings",
+                            raw_encoding.matcher, e
+                        )
+                        .red()
+                    );
+                    return None;
+                }
+            };
+
+            match Encoding::for_label(raw_encoding.encoding.trim().as_bytes()) {
+                Some(encoding) => Some(FileEncoding { matcher, encoding }),
+                None => {
+                    eprintln!(
+                        "{}: Unknown encoding '{}'",
+                        format!("Encoding for '{}' has an issue", raw_encoding.matcher).red(),
+                        raw_encoding.encoding
+                    );
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
+fn read_delimiters<'cfg>(raw_delimiters: &[RawDelimiters<'cfg>]) -> Vec<Delimiters<'cfg>> {
+    raw_delimiters
+        .iter()
+        .filter_map(|raw_delimiters| {
+            let matcher = match glob(raw_delimiters.matcher) {
+                Ok(matcher) => matcher,
+                Err(e) => {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "Failed to parse glob expression {} ({}) in delimiters",
+                            raw_delimiters.matcher, e
+                        )
+                        .red()
+                    );
+                    return None;
+                }
+            };
+
+            let (start, end) = (raw_delimiters.start.trim(), raw_delimiters.end.trim());
+
+            if start.is_empty() || end.is_empty() {
+                eprintln!(
+                    "{}: The start and end delimiters must not be blank",
+                    format!("Delimiters for '{}' have an issue", raw_delimiters.matcher).red()
+                );
+                return None;
+            }
+
+            Some(Delimiters {
+                matcher,
+                start,
+                end,
+            })
+        })
+        .collect()
+}
+
+fn read_conflicts(raw_conflicts: Option<RawConflicts>) -> Conflicts {
+    let raw_conflicts = raw_conflicts.unwrap_or_default();
+
+    let strategy = match raw_conflicts.strategy.map(|it| it.trim()) {
+        Some(raw_strategy) => ConflictStrategy::parse(raw_strategy).unwrap_or_else(|| {
+            eprintln!(
+                "{}: Unknown strategy '{}' (expected {}), using 'error' instead",
+                "Conflict resolution has an issue".red(),
+                raw_strategy,
+                ConflictStrategy::VALUES.join(", ")
+            );
+
+            ConflictStrategy::Error
+        }),
+        None => ConflictStrategy::Error,
+    };
+
+    let priority = map_glob_matchers(raw_conflicts.priority.as_ref(), "conflicts.priority")
+        .unwrap_or_default();
+
+    if strategy == ConflictStrategy::Priority && priority.is_empty() {
+        eprintln!(
+            "{}: The strategy is 'priority' but no priority globs were specified",
+            "Conflict resolution has an issue".red()
+        );
+    }
+
+    Conflicts { strategy, priority }
+}
+
+fn read_hooks(raw_hooks: Option<RawHooks>) -> io::Result<Hooks> {
+    let raw_hooks = raw_hooks.unwrap_or_default();
+
+    Ok(Hooks {
+        pre_generate: read_hook_specs(&raw_hooks.pre_generate.unwrap_or_default(), true)?,
+        post_generate: read_hook_specs(&raw_hooks.post_generate.unwrap_or_default(), false)?,
+    })
+}
+
+/// Invalid hooks are skipped with a warning if they aren't fatal. Skipping a fatal hook would
+/// generate the project without a step the template requires, so these are errors
+fn read_hook_specs<'cfg>(
+    raw_hooks: &[RawHook<'cfg>],
+    is_pre_generate: bool,
+) -> io::Result<Vec<HookSpec<'cfg>>> {
+    let mut hook_specs = vec![];
+
+    for raw_hook in raw_hooks {
+        let fatal = raw_hook.fatal.unwrap_or(true);
+
+        match read_hook_spec(raw_hook, is_pre_generate, fatal) {
+            Ok(hook_spec) => hook_specs.push(hook_spec),
+            Err(issue) if fatal => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!(
+                        "Hook '{}' has an issue: {}",
+                        raw_hook.command.join(" "),
+                        issue
+                    ),
+                ))
+            }
+            Err(issue) => eprintln!(
+                "{}: {}",
+                format!("Hook '{}' has an issue", raw_hook.command.join(" ")).dark_yellow(),
+                issue
+            ),
+        }
+    }
+
+    Ok(hook_specs)
+}
+
+fn read_hook_spec<'cfg>(
+    raw_hook: &RawHook<'cfg>,
+    is_pre_generate: bool,
+    fatal: bool,
+) -> Result<HookSpec<'cfg>, String> {
+    if raw_hook
+        .command
+        .first()
+        .is_none_or(|program| program.trim().is_empty())
+    {
+        return Err("The command must not be empty".into());
+    }
+
+    let working_dir = match raw_hook.working_dir.map(|it| it.trim()) {
+        Some(_) if is_pre_generate => {
+            return Err(
+                "Pre-generate hooks always run in a temporary directory, they can't specify a working directory"
+                    .into(),
+            )
+        }
+        Some(dir) if !is_inside_template(dir) => {
+            return Err("The working directory must be a relative path inside the target".into())
+        }
+        working_dir => working_dir,
+    };
+
+    let when = match &raw_hook.when {
+        Some(when) => {
+            Some(Expression::parse(when).map_err(|err| format!("Invalid condition ({})", err))?)
+        }
+        None => None,
+    };
+
+    Ok(HookSpec {
+        command: raw_hook.command.clone(),
+        working_dir,
+        when,
+        fatal,
+    })
+}
+
 fn read_filters(raw_filters: RawFilters) -> Filters {
     let cond_files_specs = raw_filters
         .conditional_files
         .unwrap_or_default()
         .iter()
         .filter_map(|raw_cond_templates| {
-            if raw_cond_templates.matcher.trim().is_empty() {
-                eprintln!(
-                    "{}",
-                    format!(
-                        "Matcher for condition {} is blank",
-                        raw_cond_templates.condition
-                    )
-                    .red()
-                );
-                return None;
-            }
+            let condition = match (raw_cond_templates.condition, raw_cond_templates.when) {
+                (Some(condition), None) if !condition.trim().is_empty() => {
+                    Condition::Handlebars(condition.trim())
+                }
+                (None, Some(when)) if !when.trim().is_empty() => match Expression::parse(when) {
+                    Ok(expression) => Condition::Expression(when.trim(), expression),
+                    Err(err) => {
+                        eprintln!(
+                            "{}",
+                            format!(
+                                "Invalid condition for matcher {} ({})",
+                                raw_cond_templates.matcher, err
+                            )
+                            .red()
+                        );
+                        return None;
+                    }
+                },
+                (Some(_), Some(_)) => {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "Matcher {} specifies both condition and when",
+                            raw_cond_templates.matcher
+                        )
+                        .red()
+                    );
+                    return None;
+                }
+                _ => {
+                    eprintln!(
+                        "{}",
+                        format!(
+                            "Condition for matcher {} is blank",
+                            raw_cond_templates.matcher
+                        )
+                        .red()
+                    );
+                    return None;
+                }
+            };
 
-            if raw_cond_templates.condition.trim().is_empty() {
+            if raw_cond_templates.matcher.trim().is_empty() {
                 eprintln!(
                     "{}",
-                    format!(
-                        "Condition for matcher {} is blank",
-                        raw_cond_templates.matcher
-                    )
-                    .red()
+                    format!("Matcher for condition {} is blank", condition).red()
                 );
                 return None;
             }
 
             match glob(raw_cond_templates.matcher) {
-                Ok(matcher) => Some(ConditionalFilesSpec {
-                    condition: raw_cond_templates.condition.trim(),
-                    matcher,
-                }),
+                Ok(matcher) => Some(ConditionalFilesSpec { condition, matcher }),
                 Err(e) => {
                     eprintln!(
                         "{}",
@@ -335,6 +789,7 @@ fn read_filters(raw_filters: RawFilters) -> Filters {
         exclude: map_glob_matchers(raw_filters.exclude.as_ref(), "exclude").unwrap_or_default(),
         templates: map_glob_matchers(raw_filters.templates.as_ref(), "templates"),
         non_templates: map_glob_matchers(raw_filters.non_templates.as_ref(), "nonTemplates"),
+        raw: map_glob_matchers(raw_filters.raw.as_ref(), "raw").unwrap_or_default(),
     }
 }
 
@@ -383,7 +838,7 @@ fn check_context_tree<'cfg>(
                     check_context_tree(map, &names[1..])
                 }
             }
-            ValueMapItem::Value(_) => {
+            ValueMapItem::Value => {
                 // Duplicate item, can't proceed
                 false
             }
@@ -391,10 +846,7 @@ fn check_context_tree<'cfg>(
     } else {
         // Name not found in tree, we populate, my brothers
         if names.len() == 1 {
-            tree.insert(
-                first_name.to_string(),
-                ValueMapItem::Value(first_name.to_string()),
-            );
+            tree.insert(first_name.to_string(), ValueMapItem::Value);
 
             true
         } else {
@@ -415,77 +867,145 @@ fn check_context_tree<'cfg>(
 fn read_default_value(
     question: &RawQuestion,
     must_be_identifier: bool,
+) -> io::Result<Option<Value>> {
+    read_default_value_with_env(question, must_be_identifier, &|name| var(name).ok())
+}
+
+/// Reads the default value, looking up environment variables for providers using `env`
+fn read_default_value_with_env(
+    question: &RawQuestion,
+    must_be_identifier: bool,
+    env: &dyn Fn(&str) -> Option<String>,
 ) -> io::Result<Option<Value>> {
     match question.default.as_ref() {
-        Some(value) => match question.question_type {
-            RawQuestionType::Option => match value {
-                it @ Value::Bool(_) => Ok(Some(it.clone())),
-                _ => Err(Error::new(
-                    ErrorKind::InvalidData,
-                    format!("Invalid default value for 'Option': {}", value),
-                )),
-            },
-            RawQuestionType::Selection => match value {
-                it @ Value::String(value) => {
-                    if must_be_identifier && !is_identifier(value) {
-                        Err(Error::new(
-                            ErrorKind::InvalidData,
-                            format!("Default value is not an identifier: {}", value),
-                        ))
-                    } else {
-                        Ok(Some(it.clone()))
+        Some(Value::Object(provider)) => {
+            match resolve_default_provider(provider, &question.question_type, env)? {
+                Some(value) => match check_default_value(question, &value, must_be_identifier) {
+                    Ok(value) => Ok(value),
+                    Err(err) => {
+                        eprintln!(
+                            "{}: Ignoring the value provided by {} ({})",
+                            format!("Question '{}' has an issue", question.name).dark_yellow(),
+                            Value::Object(provider.clone()),
+                            err
+                        );
+
+                        Ok(None)
                     }
+                },
+                None => Ok(None),
+            }
+        }
+        Some(value) => check_default_value(question, value, must_be_identifier),
+        None => Ok(None),
+    }
+}
+
+fn resolve_default_provider(
+    provider: &Map<String, Value>,
+    question_type: &RawQuestionType,
+    env: &dyn Fn(&str) -> Option<String>,
+) -> io::Result<Option<Value>> {
+    let mut entries = provider.iter();
+
+    let raw_value = match (entries.next(), entries.next()) {
+        (Some((kind, Value::String(name))), None) if kind == "env" => env(name.trim()),
+        (Some((kind, Value::String(name))), None) if kind == "gitConfig" => {
+            read_git_config_value(name.trim())
+        }
+        _ => {
+            return Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Invalid default value provider, expected {{\"env\": ...}} or {{\"gitConfig\": ...}}: {}",
+                    Value::Object(provider.clone())
+                ),
+            ))
+        }
+    };
+
+    Ok(raw_value.map(|raw_value| match question_type {
+        RawQuestionType::Option => match raw_value.trim().to_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => Value::Bool(true),
+            "false" | "no" | "off" | "0" => Value::Bool(false),
+            _ => Value::String(raw_value),
+        },
+        _ => Value::String(raw_value),
+    }))
+}
+
+fn check_default_value(
+    question: &RawQuestion,
+    value: &Value,
+    must_be_identifier: bool,
+) -> io::Result<Option<Value>> {
+    match question.question_type {
+        RawQuestionType::Option => match value {
+            it @ Value::Bool(_) => Ok(Some(it.clone())),
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid default value for 'Option': {}", value),
+            )),
+        },
+        RawQuestionType::Selection => match value {
+            it @ Value::String(value) => {
+                if must_be_identifier && !is_identifier(value) {
+                    Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Default value is not an identifier: {}", value),
+                    ))
+                } else {
+                    Ok(Some(it.clone()))
                 }
-                it @ Value::Array(list) => {
-                    if list.iter().any(|item| {
-                        if let Value::String(value) = item {
-                            must_be_identifier && !is_identifier(value)
-                        } else {
-                            true
-                        }
-                    }) {
-                        Err(Error::new(
-                            ErrorKind::InvalidData,
-                            format!(
-                                "Invalid default value, contains non-{}: {:?}",
-                                if must_be_identifier {
-                                    "identifiers"
-                                } else {
-                                    "strings"
-                                },
-                                list
-                            ),
-                        ))
+            }
+            it @ Value::Array(list) => {
+                if list.iter().any(|item| {
+                    if let Value::String(value) = item {
+                        must_be_identifier && !is_identifier(value)
                     } else {
-                        Ok(Some(it.clone()))
+                        true
                     }
+                }) {
+                    Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!(
+                            "Invalid default value, contains non-{}: {:?}",
+                            if must_be_identifier {
+                                "identifiers"
+                            } else {
+                                "strings"
+                            },
+                            list
+                        ),
+                    ))
+                } else {
+                    Ok(Some(it.clone()))
                 }
-                _ => Err(Error::new(
-                    ErrorKind::InvalidData,
-                    format!("Invalid default value for 'Selection': {}", value),
-                )),
-            },
-            _ => match value {
-                it @ Value::String(value) => {
-                    if must_be_identifier && !is_identifier(value) {
-                        Err(Error::new(
-                            ErrorKind::InvalidData,
-                            format!("Default value is not an identifier: {}", value),
-                        ))
-                    } else {
-                        Ok(Some(it.clone()))
-                    }
+            }
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("Invalid default value for 'Selection': {}", value),
+            )),
+        },
+        _ => match value {
+            it @ Value::String(value) => {
+                if must_be_identifier && !is_identifier(value) {
+                    Err(Error::new(
+                        ErrorKind::InvalidData,
+                        format!("Default value is not an identifier: {}", value),
+                    ))
+                } else {
+                    Ok(Some(it.clone()))
                 }
-                _ => Err(Error::new(
-                    ErrorKind::InvalidData,
-                    format!(
-                        "Invalid default value for '{:?}': {}",
-                        question.question_type, value
-                    ),
-                )),
-            },
+            }
+            _ => Err(Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Invalid default value for '{:?}': {}",
+                    question.question_type, value
+                ),
+            )),
         },
-        None => Ok(None),
     }
 }
 
@@ -529,10 +1049,87 @@ fn get_default_bool(value: Option<Value>) -> Option<bool> {
 struct ConfigJson<'cfg> {
     name: Option<&'cfg str>,
     version: Option<&'cfg str>,
+    data: Option<Vec<RawDataFile<'cfg>>>,
+    #[serde(rename(deserialize = "partialsDir", serialize = "partialsDir"))]
+    partials_dir: Option<&'cfg str>,
+    helpers: Option<Vec<RawHelper<'cfg>>>,
+    #[serde(borrow, rename(deserialize = "escapeModes", serialize = "escapeModes"))]
+    escape_modes: Option<HashMap<&'cfg str, &'cfg str>>,
+    strict: Option<bool>,
+    conflicts: Option<RawConflicts<'cfg>>,
+    hooks: Option<RawHooks<'cfg>>,
+    #[serde(borrow)]
+    modes: Option<Vec<RawFileMode<'cfg>>>,
+    #[serde(borrow)]
+    encodings: Option<Vec<RawFileEncoding<'cfg>>>,
+    #[serde(borrow)]
+    delimiters: Option<Vec<RawDelimiters<'cfg>>>,
     questions: Option<Vec<RawQuestion<'cfg>>>,
     filters: Option<RawFilters<'cfg>>,
 }
 
+#[derive(Deserialize, Serialize)]
+struct RawHelper<'cfg> {
+    name: &'cfg str,
+    script: Option<&'cfg str>,
+    #[serde(borrow)]
+    command: Option<Vec<&'cfg str>>,
+    timeout: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawFileMode<'cfg> {
+    matcher: &'cfg str,
+    mode: &'cfg str,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawFileEncoding<'cfg> {
+    matcher: &'cfg str,
+    encoding: &'cfg str,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawDelimiters<'cfg> {
+    matcher: &'cfg str,
+    start: &'cfg str,
+    end: &'cfg str,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct RawConflicts<'cfg> {
+    strategy: Option<&'cfg str>,
+    #[serde(borrow)]
+    priority: Option<Vec<&'cfg str>>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct RawHooks<'cfg> {
+    #[serde(borrow, rename(deserialize = "preGenerate", serialize = "preGenerate"))]
+    pre_generate: Option<Vec<RawHook<'cfg>>>,
+    #[serde(
+        borrow,
+        rename(deserialize = "postGenerate", serialize = "postGenerate")
+    )]
+    post_generate: Option<Vec<RawHook<'cfg>>>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawHook<'cfg> {
+    // Owned, so commands can contain escaped characters
+    command: Vec<String>,
+    #[serde(rename(deserialize = "workingDir", serialize = "workingDir"))]
+    working_dir: Option<&'cfg str>,
+    when: Option<String>,
+    fatal: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct RawDataFile<'cfg> {
+    name: &'cfg str,
+    file: &'cfg str,
+}
+
 #[derive(Deserialize, Serialize)]
 struct RawQuestion<'cfg> {
     name: &'cfg str,
@@ -543,6 +1140,7 @@ struct RawQuestion<'cfg> {
     multi: Option<bool>,
     format: Option<&'cfg str>,
     default: Option<Value>,
+    secret: Option<bool>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -568,17 +1166,19 @@ struct RawFilters<'cfg> {
     templates: Option<Vec<&'cfg str>>,
     #[serde(rename(deserialize = "nonTemplates", serialize = "nonTemplates"))]
     non_templates: Option<Vec<&'cfg str>>,
+    raw: Option<Vec<&'cfg str>>,
 }
 
 #[derive(Deserialize, Serialize)]
 struct RawConditionalFiles<'cfg> {
-    condition: &'cfg str,
+    condition: Option<&'cfg str>,
+    when: Option<&'cfg str>,
     matcher: &'cfg str,
 }
 
 enum ValueMapItem {
     Map(HashMap<String, ValueMapItem>),
-    Value(String),
+    Value,
 }
 
 #[derive(Debug, Serialize)]
@@ -586,6 +1186,26 @@ pub struct Config<'cfg> {
     pub name: Option<&'cfg str>,
     pub version: Option<&'cfg str>,
     #[serde(skip)]
+    pub data: Vec<DataFile<'cfg>>,
+    #[serde(skip)]
+    pub partials_dir: &'cfg str,
+    #[serde(skip)]
+    pub helpers: Vec<HelperSpec<'cfg>>,
+    #[serde(skip)]
+    pub escape_modes: HashMap<String, EscapeMode>,
+    #[serde(skip)]
+    pub strict: bool,
+    #[serde(skip)]
+    pub conflicts: Conflicts,
+    #[serde(skip)]
+    pub hooks: Hooks<'cfg>,
+    #[serde(skip)]
+    pub modes: Vec<FileMode>,
+    #[serde(skip)]
+    pub encodings: Vec<FileEncoding>,
+    #[serde(skip)]
+    pub delimiters: Vec<Delimiters<'cfg>>,
+    #[serde(skip)]
     pub questions: Vec<Question<'cfg>>,
     #[serde(skip)]
     pub filters: Filters<'cfg>,
@@ -596,10 +1216,91 @@ impl<'cfg> Config<'cfg> {
         Config {
             name: None,
             version: None,
+            data: vec![],
+            partials_dir: DEFAULT_PARTIALS_DIR,
+            helpers: vec![],
+            escape_modes: HashMap::new(),
+            strict: false,
+            conflicts: Conflicts::default(),
+            hooks: Hooks::default(),
+            modes: vec![],
+            encodings: vec![],
+            delimiters: vec![],
             questions: vec![],
             filters: Filters::empty(),
         }
     }
+
+    /// The escape mode for a file is chosen by its extension, defaulting to HTML
+    pub fn escape_mode(&self, path: &Path) -> EscapeMode {
+        path.extension()
+            .and_then(|it| {
+                self.escape_modes
+                    .get(&it.to_string_lossy().to_lowercase())
+                    .copied()
+            })
+            .unwrap_or(EscapeMode::Html)
+    }
+
+    /// The configured mode of a file relative to the template directory, the last matching glob
+    /// wins
+    pub fn file_mode(&self, path: &Path) -> Option<u32> {
+        self.modes
+            .iter()
+            .rev()
+            .find(|it| it.matcher.is_match(path))
+            .map(|it| it.mode)
+    }
+
+    /// The declared encoding of a text file relative to the template directory, the last
+    /// matching glob wins
+    pub fn encoding(&self, path: &Path) -> Option<&'static Encoding> {
+        self.encodings
+            .iter()
+            .rev()
+            .find(|it| it.matcher.is_match(path))
+            .map(|it| it.encoding)
+    }
+
+    /// The delimiters of Handlebars expressions in a file relative to the template directory, if
+    /// they aren't `{{` and `}}`. The last matching glob wins
+    pub fn delimiters(&self, path: &Path) -> Option<&Delimiters<'cfg>> {
+        self.delimiters
+            .iter()
+            .rev()
+            .find(|it| it.matcher.is_match(path))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct DataFile<'cfg> {
+    pub path: QuestionPath<'cfg>,
+    pub file: &'cfg str,
+    pub format: DataFormat,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DataFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct HelperSpec<'cfg> {
+    pub name: &'cfg str,
+    pub source: HelperSource<'cfg>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum HelperSource<'cfg> {
+    /// A Rhai script, relative to the root directory of the template
+    Script(&'cfg str),
+    /// An executable and its arguments, communicating using JSON on stdin and stdout
+    Command {
+        command: Vec<&'cfg str>,
+        timeout: Duration,
+    },
 }
 
 #[derive(Debug, PartialEq)]
@@ -645,6 +1346,7 @@ pub enum QuestionSpec<'cfg> {
     },
     Text {
         default: Option<String>,
+        secret: bool,
     },
     Custom {
         format: &'cfg str,
@@ -652,6 +1354,62 @@ pub enum QuestionSpec<'cfg> {
     },
 }
 
+/// How multiple source files rendered to the same target are resolved
+#[derive(Debug)]
+pub struct Conflicts {
+    pub strategy: ConflictStrategy,
+    pub priority: Vec<GlobMatcher>,
+}
+
+impl Default for Conflicts {
+    fn default() -> Self {
+        Conflicts {
+            strategy: ConflictStrategy::Error,
+            priority: vec![],
+        }
+    }
+}
+
+/// The Unix permissions of generated files matching the glob
+#[derive(Debug)]
+pub struct FileMode {
+    pub matcher: GlobMatcher,
+    pub mode: u32,
+}
+
+/// The encoding of text files matching the glob that aren't UTF-8
+#[derive(Debug)]
+pub struct FileEncoding {
+    pub matcher: GlobMatcher,
+    pub encoding: &'static Encoding,
+}
+
+/// Alternate delimiters of Handlebars expressions in files matching the glob, e.g. `<%` and `%>`
+#[derive(Debug)]
+pub struct Delimiters<'cfg> {
+    pub matcher: GlobMatcher,
+    pub start: &'cfg str,
+    pub end: &'cfg str,
+}
+
+#[derive(Debug, Default)]
+pub struct Hooks<'cfg> {
+    /// Run in the template directory after the questions were answered, before rendering
+    pub pre_generate: Vec<HookSpec<'cfg>>,
+    /// Run in the target directory once the project was generated
+    pub post_generate: Vec<HookSpec<'cfg>>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct HookSpec<'cfg> {
+    pub command: Vec<String>,
+    /// Relative to the directory the hook runs in
+    pub working_dir: Option<&'cfg str>,
+    pub when: Option<Expression>,
+    /// Whether a failure of the hook fails the whole generation
+    pub fatal: bool,
+}
+
 #[derive(Debug)]
 pub struct Filters<'cfg> {
     pub conditional_files: Vec<ConditionalFilesSpec<'cfg>>,
@@ -659,6 +1417,8 @@ pub struct Filters<'cfg> {
     pub exclude: Vec<GlobMatcher>,
     pub templates: Option<Vec<GlobMatcher>>,
     pub non_templates: Option<Vec<GlobMatcher>>,
+    /// Always copied verbatim, even if they match `templates` or have a Handlebars extension
+    pub raw: Vec<GlobMatcher>,
 }
 
 impl<'cfg> Filters<'cfg> {
@@ -669,18 +1429,36 @@ impl<'cfg> Filters<'cfg> {
             exclude: vec![],
             templates: None,
             non_templates: None,
+            raw: vec![],
         }
     }
 }
 
 #[derive(Debug)]
 pub struct ConditionalFilesSpec<'cfg> {
-    pub condition: &'cfg str,
+    pub condition: Condition<'cfg>,
     pub matcher: GlobMatcher,
 }
 
+#[derive(Debug, PartialEq)]
+pub enum Condition<'cfg> {
+    /// A Handlebars expression that is rendered and checked for a "truthy" result
+    Handlebars(&'cfg str),
+    /// A boolean expression that is evaluated on the context
+    Expression(&'cfg str, Expression),
+}
+
+impl<'cfg> Display for Condition<'cfg> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Condition::Handlebars(source) | Condition::Expression(source, _) => f.write_str(source),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
+    use std::env::set_var;
     use std::fs;
 
     use serde_json::Number;
@@ -700,6 +1478,10 @@ mod tests {
 
     const TOOL_CONFIG: ToolConfig<'_> = ToolConfig {
         verbose: true,
+        strict: false,
+        lint: false,
+        on_existing: None,
+        on_conflict: None,
         no_history: false,
         no_init: false,
         ignore_checks: false,
@@ -729,6 +1511,16 @@ mod tests {
         let config_json = serde_json::to_string_pretty(&ConfigJson {
             name: Some("Some Template"),
             version: Some("0.1.0"),
+            data: None,
+            partials_dir: None,
+            helpers: None,
+            escape_modes: None,
+            strict: None,
+            conflicts: None,
+            hooks: None,
+            modes: None,
+            encodings: None,
+            delimiters: None,
             questions: Some(vec![
                 RawQuestion {
                     name: "author",
@@ -738,6 +1530,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "debug",
@@ -747,6 +1540,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: Some(Value::Bool(true)),
+                    secret: None,
                 },
                 RawQuestion {
                     name: "main.package",
@@ -756,6 +1550,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "main.features",
@@ -768,6 +1563,7 @@ mod tests {
                         Value::String("feature_2".into()),
                         Value::String("feature_3".into()),
                     ])),
+                    secret: None,
                 },
                 RawQuestion {
                     name: "customStuff",
@@ -777,6 +1573,7 @@ mod tests {
                     items: None,
                     multi: None,
                     default: None,
+                    secret: None,
                 },
             ]),
             filters: None,
@@ -790,13 +1587,26 @@ mod tests {
             Config {
                 name: Some("Some Template"),
                 version: Some("0.1.0"),
+                data: vec![],
+                partials_dir: DEFAULT_PARTIALS_DIR,
+                helpers: vec![],
+                escape_modes: HashMap::new(),
+                strict: false,
+                conflicts: Conflicts::default(),
+                hooks: Hooks::default(),
+                modes: vec![],
+                encodings: vec![],
+                delimiters: vec![],
                 questions: vec![
                     Question {
                         path: QuestionPath {
                             names: vec!["author"]
                         },
                         pretty: Some("Who is the author of this project?"),
-                        spec: QuestionSpec::Text { default: None },
+                        spec: QuestionSpec::Text {
+                            default: None,
+                            secret: false
+                        },
                     },
                     Question {
                         path: QuestionPath {
@@ -849,6 +1659,16 @@ mod tests {
             Config {
                 name: Some("Some Template"),
                 version: None,
+                data: vec![],
+                partials_dir: DEFAULT_PARTIALS_DIR,
+                helpers: vec![],
+                escape_modes: HashMap::new(),
+                strict: false,
+                conflicts: Conflicts::default(),
+                hooks: Hooks::default(),
+                modes: vec![],
+                encodings: vec![],
+                delimiters: vec![],
                 questions: vec![],
                 filters: Filters::empty(),
             }
@@ -860,6 +1680,16 @@ mod tests {
         let malformed_names_json = serde_json::to_string_pretty(&ConfigJson {
             name: Some("Some Template"),
             version: Some("0.1.0"),
+            data: None,
+            partials_dir: None,
+            helpers: None,
+            escape_modes: None,
+            strict: None,
+            conflicts: None,
+            hooks: None,
+            modes: None,
+            encodings: None,
+            delimiters: None,
             questions: Some(vec![
                 RawQuestion {
                     name: "&author",
@@ -869,6 +1699,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "1.debug",
@@ -878,6 +1709,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "main..package",
@@ -887,6 +1719,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "",
@@ -896,6 +1729,7 @@ mod tests {
                     pretty: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "__template__.something",
@@ -905,6 +1739,7 @@ mod tests {
                     pretty: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "customStuff",
@@ -914,6 +1749,7 @@ mod tests {
                     items: None,
                     multi: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "otherCustomStuff",
@@ -923,6 +1759,7 @@ mod tests {
                     pretty: None,
                     items: None,
                     multi: None,
+                    secret: None,
                 },
             ]),
             filters: None,
@@ -934,6 +1771,16 @@ mod tests {
             Config {
                 name: Some("Some Template"),
                 version: Some("0.1.0"),
+                data: vec![],
+                partials_dir: DEFAULT_PARTIALS_DIR,
+                helpers: vec![],
+                escape_modes: HashMap::new(),
+                strict: false,
+                conflicts: Conflicts::default(),
+                hooks: Hooks::default(),
+                modes: vec![],
+                encodings: vec![],
+                delimiters: vec![],
                 questions: vec![],
                 filters: Filters::empty(),
             }
@@ -942,6 +1789,16 @@ mod tests {
         let malformed_context_tree = serde_json::to_string_pretty(&ConfigJson {
             name: Some("Some Template"),
             version: Some("0.1.0"),
+            data: None,
+            partials_dir: None,
+            helpers: None,
+            escape_modes: None,
+            strict: None,
+            conflicts: None,
+            hooks: None,
+            modes: None,
+            encodings: None,
+            delimiters: None,
             questions: Some(vec![
                 RawQuestion {
                     name: "author",
@@ -951,6 +1808,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: Some(Value::String("You".into())),
+                    secret: None,
                 },
                 RawQuestion {
                     name: "author.email",
@@ -960,6 +1818,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "author.email.domain",
@@ -969,6 +1828,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
             ]),
             filters: None,
@@ -980,13 +1840,24 @@ mod tests {
             Config {
                 name: Some("Some Template"),
                 version: Some("0.1.0"),
+                data: vec![],
+                partials_dir: DEFAULT_PARTIALS_DIR,
+                helpers: vec![],
+                escape_modes: HashMap::new(),
+                strict: false,
+                conflicts: Conflicts::default(),
+                hooks: Hooks::default(),
+                modes: vec![],
+                encodings: vec![],
+                delimiters: vec![],
                 questions: vec![Question {
                     path: QuestionPath {
                         names: vec!["author"]
                     },
                     pretty: Some("Who is the author of this project?"),
                     spec: QuestionSpec::Text {
-                        default: Some("You".into())
+                        default: Some("You".into()),
+                        secret: false,
                     },
                 },],
                 filters: Filters::empty(),
@@ -996,6 +1867,16 @@ mod tests {
         let malformed_selection_items = serde_json::to_string_pretty(&ConfigJson {
             name: None,
             version: None,
+            data: None,
+            partials_dir: None,
+            helpers: None,
+            escape_modes: None,
+            strict: None,
+            conflicts: None,
+            hooks: None,
+            modes: None,
+            encodings: None,
+            delimiters: None,
             questions: Some(vec![
                 RawQuestion {
                     name: "features1",
@@ -1005,6 +1886,7 @@ mod tests {
                     multi: Some(true),
                     format: None,
                     default: None,
+                    secret: None,
                 },
                 RawQuestion {
                     name: "features2",
@@ -1014,6 +1896,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: Some(Value::Array(vec!["feature2".into()])),
+                    secret: None,
                 },
                 RawQuestion {
                     name: "features3",
@@ -1023,6 +1906,7 @@ mod tests {
                     multi: None,
                     format: None,
                     default: None,
+                    secret: None,
                 },
             ]),
             filters: None,
@@ -1034,6 +1918,16 @@ mod tests {
             Config {
                 name: None,
                 version: None,
+                data: vec![],
+                partials_dir: DEFAULT_PARTIALS_DIR,
+                helpers: vec![],
+                escape_modes: HashMap::new(),
+                strict: false,
+                conflicts: Conflicts::default(),
+                hooks: Hooks::default(),
+                modes: vec![],
+                encodings: vec![],
+                delimiters: vec![],
                 questions: vec![Question {
                     path: QuestionPath {
                         names: vec!["features2"]
@@ -1050,6 +1944,314 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_read_config_data() {
+        let config = read_config(
+            r#"{
+    "data": [
+        { "name": "lookup.regions", "file": ".architect/data/regions.yaml" },
+        { "name": "javaVersions", "file": "java.JSON" },
+        { "name": "tool", "file": "tool.toml" },
+        { "name": "unsupported", "file": "data.xml" },
+        { "name": "__template__.data", "file": "data.json" },
+        { "name": "lookup", "file": "lookup.json" }
+    ],
+    "questions": [
+        { "name": "javaVersions.selected", "type": "Text" },
+        { "name": "author", "type": "Text" }
+    ]
+}"#,
+            &TOOL_CONFIG,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.data,
+            vec![
+                DataFile {
+                    path: QuestionPath {
+                        names: vec!["lookup", "regions"]
+                    },
+                    file: ".architect/data/regions.yaml",
+                    format: DataFormat::Yaml,
+                },
+                DataFile {
+                    path: QuestionPath {
+                        names: vec!["javaVersions"]
+                    },
+                    file: "java.JSON",
+                    format: DataFormat::Json,
+                },
+                DataFile {
+                    path: QuestionPath {
+                        names: vec!["tool"]
+                    },
+                    file: "tool.toml",
+                    format: DataFormat::Toml,
+                },
+            ]
+        );
+
+        assert_eq!(
+            config.questions,
+            vec![Question {
+                path: QuestionPath {
+                    names: vec!["author"]
+                },
+                pretty: None,
+                spec: QuestionSpec::Text {
+                    default: None,
+                    secret: false
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn test_read_helpers() {
+        let raw_helpers: Vec<RawHelper> = serde_json::from_str(
+            r#"[
+    { "name": "percent", "script": ".architect/helpers/percent.rhai" },
+    { "name": "in-valid", "script": "invalid.rhai" },
+    { "name": "percent", "script": "duplicate.rhai" },
+    { "name": "outside", "script": "../outside.rhai" },
+    { "name": "absolute", "script": "/absolute.rhai" },
+    { "name": "libVersion", "command": ["./tools/lib-version", "--latest"], "timeout": 500 },
+    { "name": "fromPath", "command": [" lookup "] },
+    { "name": "emptyCommand", "command": [] },
+    { "name": "zeroTimeout", "command": ["lookup"], "timeout": 0 },
+    { "name": "both", "script": "both.rhai", "command": ["both"] },
+    { "name": "neither" }
+]"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_helpers(&raw_helpers),
+            vec![
+                HelperSpec {
+                    name: "percent",
+                    source: HelperSource::Script(".architect/helpers/percent.rhai"),
+                },
+                HelperSpec {
+                    name: "libVersion",
+                    source: HelperSource::Command {
+                        command: vec!["./tools/lib-version", "--latest"],
+                        timeout: Duration::from_millis(500),
+                    },
+                },
+                HelperSpec {
+                    name: "fromPath",
+                    source: HelperSource::Command {
+                        command: vec!["lookup"],
+                        timeout: DEFAULT_HELPER_TIMEOUT,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_read_hook_specs() {
+        let raw_hooks: Vec<RawHook> = serde_json::from_str(
+            r#"[
+    { "command": ["npm", "install"], "workingDir": "frontend", "when": "frontend == \"npm\"" },
+    { "command": ["chmod", "+x", "gradlew"], "fatal": false },
+    { "command": [], "fatal": false },
+    { "command": ["outside"], "workingDir": "../outside", "fatal": false },
+    { "command": ["invalid"], "when": "a ==", "fatal": false }
+]"#,
+        )
+        .unwrap();
+
+        // Invalid hooks that aren't fatal are skipped
+        assert_eq!(
+            read_hook_specs(&raw_hooks, false).unwrap(),
+            vec![
+                HookSpec {
+                    command: vec!["npm".into(), "install".into()],
+                    working_dir: Some("frontend"),
+                    when: Some(Expression::parse("frontend == 'npm'").unwrap()),
+                    fatal: true,
+                },
+                HookSpec {
+                    command: vec!["chmod".into(), "+x".into(), "gradlew".into()],
+                    working_dir: None,
+                    when: None,
+                    fatal: false,
+                },
+            ]
+        );
+
+        // Invalid fatal hooks fail reading the configuration, instead of silently skipping them
+        for invalid_hook in [
+            r#"{ "command": [] }"#,
+            r#"{ "command": ["outside"], "workingDir": "../outside" }"#,
+            r#"{ "command": ["invalid"], "when": "a ==", "fatal": true }"#,
+        ] {
+            let raw_hooks = vec![serde_json::from_str::<RawHook>(invalid_hook).unwrap()];
+            assert!(read_hook_specs(&raw_hooks, false).is_err());
+        }
+
+        let raw_hooks: Vec<RawHook> =
+            serde_json::from_str(r#"[{ "command": ["./check.sh"], "workingDir": "scripts" }]"#)
+                .unwrap();
+        assert!(read_hook_specs(&raw_hooks, true).is_err());
+
+        let config = read_config(
+            r#"{ "hooks": { "postGenerate": [{ "command": ["npm", "install"], "when": "npm ==" }] } }"#,
+            &TOOL_CONFIG,
+        );
+        assert!(config.is_err());
+    }
+
+    #[test]
+    fn test_escape_modes() {
+        let config = read_config(
+            r#"{
+    "escapeModes": {
+        ".json": "json",
+        "YAML": "yaml",
+        "sh": "Shell",
+        "md": "markdown",
+        "": "xml"
+    }
+}"#,
+            &TOOL_CONFIG,
+        )
+        .unwrap();
+
+        assert_eq!(3, config.escape_modes.len());
+        assert_eq!(
+            EscapeMode::Json,
+            config.escape_mode(Path::new("package.json"))
+        );
+        assert_eq!(
+            EscapeMode::Yaml,
+            config.escape_mode(Path::new("config/application.yaml"))
+        );
+        assert_eq!(EscapeMode::Shell, config.escape_mode(Path::new("run.SH")));
+        assert_eq!(EscapeMode::Html, config.escape_mode(Path::new("README.md")));
+        assert_eq!(EscapeMode::Html, config.escape_mode(Path::new("Makefile")));
+    }
+
+    #[test]
+    fn test_file_modes() {
+        let config = read_config(
+            r#"{
+    "modes": [
+        { "matcher": "**/*.sh*", "mode": "755" },
+        { "matcher": "gradlew*", "mode": "0750" },
+        { "matcher": "secret/**", "mode": "600" },
+        { "matcher": "secret/**", "mode": "rw-------" },
+        { "matcher": "[", "mode": "644" }
+    ]
+}"#,
+            &TOOL_CONFIG,
+        )
+        .unwrap();
+
+        assert_eq!(3, config.modes.len());
+        assert_eq!(Some(0o755), config.file_mode(Path::new("bin/run.sh.hbs")));
+        assert_eq!(Some(0o750), config.file_mode(Path::new("gradlew")));
+        assert_eq!(Some(0o600), config.file_mode(Path::new("secret/key")));
+        assert_eq!(None, config.file_mode(Path::new("README.md")));
+    }
+
+    #[test]
+    fn test_encodings() {
+        let config = read_config(
+            r#"{
+    "encodings": [
+        { "matcher": "**/*.properties", "encoding": "ISO-8859-1" },
+        { "matcher": "legacy/**", "encoding": "Shift_JIS" },
+        { "matcher": "**/*.txt", "encoding": "klingon" }
+    ]
+}"#,
+            &TOOL_CONFIG,
+        )
+        .unwrap();
+
+        assert_eq!(2, config.encodings.len());
+        assert_eq!(
+            Some(encoding_rs::WINDOWS_1252),
+            config.encoding(Path::new("src/messages.properties"))
+        );
+        assert_eq!(
+            Some(encoding_rs::SHIFT_JIS),
+            config.encoding(Path::new("legacy/messages.properties"))
+        );
+        assert_eq!(None, config.encoding(Path::new("README.txt")));
+    }
+
+    #[test]
+    fn test_delimiters() {
+        let config = read_config(
+            r#"{
+    "delimiters": [
+        { "matcher": "**/*.vue", "start": "<%", "end": "%>" },
+        { "matcher": ".github/**", "start": "[[", "end": "]]" },
+        { "matcher": "**/*.tmpl", "start": " ", "end": "]]" }
+    ],
+    "filters": {
+        "raw": ["**/*.go.tmpl"]
+    }
+}"#,
+            &TOOL_CONFIG,
+        )
+        .unwrap();
+
+        assert_eq!(2, config.delimiters.len());
+
+        let delimiters = config.delimiters(Path::new("src/App.vue")).unwrap();
+        assert_eq!(("<%", "%>"), (delimiters.start, delimiters.end));
+
+        let delimiters = config
+            .delimiters(Path::new(".github/workflows/ci.yml"))
+            .unwrap();
+        assert_eq!(("[[", "]]"), (delimiters.start, delimiters.end));
+
+        assert!(config.delimiters(Path::new("README.md")).is_none());
+
+        assert_eq!(1, config.filters.raw.len());
+        assert!(config.filters.raw[0].is_match("cmd/main.go.tmpl"));
+    }
+
+    #[test]
+    fn test_read_filters_conditional_files() {
+        let raw_filters: RawFilters = serde_json::from_str(
+            r#"{
+    "conditionalTemplates": [
+        { "condition": "useDocker", "matcher": "Dockerfile" },
+        { "when": "'kafka' in features && !useDocker", "matcher": "kafka/**" },
+        { "when": "features &&", "matcher": "broken" },
+        { "condition": "a", "when": "a", "matcher": "both" },
+        { "when": " ", "matcher": "blank" }
+    ]
+}"#,
+        )
+        .unwrap();
+
+        let filters = read_filters(raw_filters);
+
+        assert_eq!(
+            filters.conditional_files,
+            vec![
+                ConditionalFilesSpec {
+                    condition: Condition::Handlebars("useDocker"),
+                    matcher: glob("Dockerfile").unwrap(),
+                },
+                ConditionalFilesSpec {
+                    condition: Condition::Expression(
+                        "'kafka' in features && !useDocker",
+                        Expression::parse("'kafka' in features && !useDocker").unwrap()
+                    ),
+                    matcher: glob("kafka/**").unwrap(),
+                },
+            ]
+        );
+    }
+
     #[test]
     fn test_read_default_value() {
         let no_default = RawQuestion {
@@ -1060,6 +2262,7 @@ mod tests {
             items: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let no_default_result = read_default_value(&no_default, false);
@@ -1074,6 +2277,7 @@ mod tests {
             items: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let valid_option_default = read_default_value(&valid_option, false);
@@ -1089,6 +2293,7 @@ mod tests {
             items: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let invalid_option_default = read_default_value(&invalid_option, false);
@@ -1102,6 +2307,7 @@ mod tests {
             pretty: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let valid_selection_default = read_default_value(&valid_selection, false);
@@ -1120,6 +2326,7 @@ mod tests {
             pretty: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let invalid_selection_default = read_default_value(&invalid_selection, true);
@@ -1133,6 +2340,7 @@ mod tests {
             pretty: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let another_invalid_selection_default =
@@ -1148,6 +2356,7 @@ mod tests {
             pretty: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let valid_selection_list_default = read_default_value(&valid_selection_list, true);
@@ -1166,6 +2375,7 @@ mod tests {
             pretty: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let invalid_selection_list_default = read_default_value(&invalid_selection_list, true);
@@ -1179,6 +2389,7 @@ mod tests {
             pretty: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let another_invalid_sel_list_default = read_default_value(&another_invalid_sel_list, true);
@@ -1192,6 +2403,7 @@ mod tests {
             pretty: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let other_question_default = read_default_value(&other_question, false);
@@ -1210,16 +2422,158 @@ mod tests {
             pretty: None,
             format: None,
             multi: None,
+            secret: None,
         };
 
         let inv_id_question_default = read_default_value(&inv_id_question, true);
         assert!(inv_id_question_default.is_err());
     }
 
+    #[test]
+    fn test_read_default_value_provider() {
+        // The process environment is shared with the tests running in parallel
+        let env = |name: &str| match name {
+            "ARCHITECT_TEST_AUTHOR" => Some("Some Author".to_string()),
+            "ARCHITECT_TEST_DEBUG" => Some("yes".to_string()),
+            _ => None,
+        };
+
+        let provided_question = |question_type, provider: Value| RawQuestion {
+            name: "provided",
+            question_type,
+            default: Some(provider),
+            items: None,
+            pretty: None,
+            format: None,
+            multi: None,
+            secret: None,
+        };
+
+        assert_eq!(
+            Some(Value::String("Some Author".into())),
+            read_default_value_with_env(
+                &provided_question(
+                    RawQuestionType::Text,
+                    serde_json::json!({ "env": "ARCHITECT_TEST_AUTHOR" })
+                ),
+                false,
+                &env
+            )
+            .unwrap()
+        );
+
+        assert_eq!(
+            Some(Value::Bool(true)),
+            read_default_value_with_env(
+                &provided_question(
+                    RawQuestionType::Option,
+                    serde_json::json!({ "env": "ARCHITECT_TEST_DEBUG" })
+                ),
+                false,
+                &env
+            )
+            .unwrap()
+        );
+
+        assert_eq!(
+            None,
+            read_default_value_with_env(
+                &provided_question(
+                    RawQuestionType::Text,
+                    serde_json::json!({ "env": "ARCHITECT_TEST_UNSET" })
+                ),
+                false,
+                &env
+            )
+            .unwrap()
+        );
+
+        assert_eq!(
+            None,
+            read_default_value_with_env(
+                &provided_question(
+                    RawQuestionType::Identifier,
+                    serde_json::json!({ "env": "ARCHITECT_TEST_AUTHOR" })
+                ),
+                true,
+                &env
+            )
+            .unwrap()
+        );
+
+        assert!(read_default_value_with_env(
+            &provided_question(
+                RawQuestionType::Text,
+                serde_json::json!({ "gitConfig": "architect.test.unset" })
+            ),
+            false,
+            &env
+        )
+        .is_ok());
+
+        assert!(read_default_value_with_env(
+            &provided_question(
+                RawQuestionType::Text,
+                serde_json::json!({ "file": "/etc/passwd" })
+            ),
+            false,
+            &env
+        )
+        .is_err());
+
+        assert!(read_default_value_with_env(
+            &provided_question(
+                RawQuestionType::Text,
+                serde_json::json!({ "env": "ARCHITECT_TEST_AUTHOR", "gitConfig": "user.name" })
+            ),
+            false,
+            &env
+        )
+        .is_err());
+
+        // Only this test reads the variable, so setting it doesn't affect the tests running in
+        // parallel. Its value contains slashes, which don't match the format
+        set_var("ARCHITECT_TEST_PROVIDED_PATH", "/usr/local/bin");
+
+        let config = read_config(
+            r#"{
+    "questions": [
+        {
+            "name": "custom",
+            "type": "Custom",
+            "format": "^[a-z]+$",
+            "default": { "env": "ARCHITECT_TEST_PROVIDED_PATH" }
+        }
+    ]
+}"#,
+            &TOOL_CONFIG,
+        )
+        .unwrap();
+
+        assert_eq!(
+            config.questions,
+            vec![Question {
+                path: QuestionPath {
+                    names: vec!["custom"]
+                },
+                pretty: None,
+                spec: QuestionSpec::Custom {
+                    format: "^[a-z]+$",
+                    default: None
+                },
+            }]
+        );
+    }
+
     impl<'cfg> PartialEq for Config<'cfg> {
         fn eq(&self, other: &Self) -> bool {
             self.name == other.name
                 && self.version == other.version
+                && self.data == other.data
+                && self.partials_dir == other.partials_dir
+                && self.helpers == other.helpers
+                && self.escape_modes == other.escape_modes
+                && self.strict == other.strict
                 && self.questions == other.questions
                 && self.filters == other.filters
         }
@@ -1232,6 +2586,7 @@ mod tests {
                 && matchers_eq(&self.exclude, &other.exclude)
                 && opt_matchers_eq(&self.templates, &other.templates)
                 && opt_matchers_eq(&self.non_templates, &other.non_templates)
+                && matchers_eq(&self.raw, &other.raw)
         }
     }
 
@@ -49,6 +49,12 @@ export interface Config {
      * Can be used in handlebars templates using `__template__.version`
      */
     version?: string;
+    /**
+     * Static data files (JSON, YAML, or TOML) to load into the context.
+     *
+     * The data is available in handlebars templates before any questions are asked
+     */
+    data?: DataFile[];
     /**
      * Questions to ask the user to specify dynamic context values.
      *
@@ -59,6 +65,76 @@ export interface Config {
      * Contains multiple filters to control which files are actually considered and rendered
      */
     filters?: Filters;
+    /**
+     * The directory containing Handlebars partials, relative to the root directory of the template.
+     *
+     * All files in it are registered as partials and are never added to the target.
+     *
+     * Default: `.architect/partials`
+     */
+    partialsDir?: string;
+    /**
+     * Custom Handlebars helpers shipped with the template
+     */
+    helpers?: Helper[];
+    /**
+     * The escape modes used for values in template files, by file extension (e.g. `json`).
+     *
+     * Files with other extensions are HTML-escaped
+     */
+    escapeModes?: { [extension: string]: EscapeMode };
+    /**
+     * Fails on references to undefined values in file contents, names and Handlebars
+     * conditions instead of rendering them empty.
+     *
+     * This can also be enabled using the `--strict` flag
+     */
+    strict?: boolean;
+    /**
+     * How multiple files rendered to the same target are handled.
+     *
+     * Default: `{ "strategy": "error" }`
+     */
+    conflicts?: Conflicts;
+    /**
+     * Commands that are run at certain points of the generation
+     */
+    hooks?: Hooks;
+    /**
+     * Unix permissions of generated files, e.g. to make scripts executable.
+     *
+     * Other files keep the permissions of their source file
+     */
+    modes?: FileMode[];
+    /**
+     * Encodings of text files that aren't UTF-8.
+     *
+     * Files starting with a byte order mark don't need to be declared
+     */
+    encodings?: FileEncoding[];
+    /**
+     * Alternate delimiters of Handlebars expressions for files that contain literal `{{`, e.g.
+     * Vue components or GitHub Actions workflows
+     */
+    delimiters?: Delimiters[];
+}
+
+export interface DataFile {
+    /**
+     * The name in the context where the parsed content of the file is stored.
+     *
+     * Can be multiple names concatenated using `.` to create hierarchical structures in
+     * the context. The same rules as for question names apply.
+     *
+     * Format: `^[a-zA-Z_$][a-zA-Z0-9_$]*$`
+     */
+    name: string;
+    /**
+     * The path of the data file, relative to the template directory.
+     *
+     * The format is determined by the file extension: `.json`, `.yaml`/`.yml`, or `.toml`
+     */
+    file: string;
 }
 
 export type Question = SimpleQuestion | SelectionQuestion | CustomQuestion;
@@ -133,6 +209,12 @@ export interface Filters {
      * This property has no effect, if `templates` is also specified
      */
     nonTemplates?: string[];
+    /**
+     * Specifies Glob expressions that indicate files that are always copied verbatim.
+     *
+     * This overrides `templates` and Handlebars extensions like `.hbs`, which are kept as well
+     */
+    raw?: string[];
 }
 
 export interface ConditionalFiles {
@@ -142,9 +224,20 @@ export interface ConditionalFiles {
      * This is an expression that is handled by handlebars.
      *
      * The expression is automatically wrapped in curly braces (`{{` `}}`) so you
-     * only need to specify the actual content of the expression here
+     * only need to specify the actual content of the expression here.
+     *
+     * Either this or `when` must be specified
      */
-    condition: string;
+    condition?: string;
+    /**
+     * The condition that decides whether the matched files are created.
+     *
+     * This is a boolean expression that is evaluated directly on the context, it supports
+     * `&&`, `||`, `!`, `==`, `!=`, `in` and `contains`.
+     *
+     * Either this or `condition` must be specified
+     */
+    when?: string;
     /**
      * A Glob string specifying the files affected by the condition
      */
@@ -170,6 +263,13 @@ interface BaseQuestion {
      * processing input
      */
     pretty?: string;
+    /**
+     * Hides the input when answering this question and prevents Architect from remembering
+     * the answer for the next time the template is used.
+     *
+     * Only supported for questions of type `Text`
+     */
+    secret?: boolean;
     /**
      * The default answer for this question.
      *
@@ -177,9 +277,29 @@ interface BaseQuestion {
      * you can specify either a string or a list of strings, otherwise just a string.
      *
      * Note: Specifying a list of strings will only be accepted if the `Selection` question
-     * allows the selection of multiple items
+     * allows the selection of multiple items.
+     *
+     * Instead of a fixed value you can also specify a provider that reads the default value
+     * from an environment variable or the Git configuration when Architect runs
      */
-    default?: string | boolean | string[]
+    default?: string | boolean | string[] | DefaultProvider
+}
+
+/**
+ * Reads the default value of a question from the environment of the user.
+ *
+ * Exactly one of the properties must be specified. If the value isn't available or isn't
+ * valid for the question, the question is asked without a default value
+ */
+export interface DefaultProvider {
+    /**
+     * The name of an environment variable, e.g. `USER`
+     */
+    env?: string;
+    /**
+     * The name of a Git configuration value, e.g. `user.email`
+     */
+    gitConfig?: string;
 }
 
 export enum QuestionType {
@@ -189,3 +309,144 @@ export enum QuestionType {
     Text = 'Text',
     Custom = 'Custom'
 }
+
+export interface Helper {
+    /**
+     * The name of the helper used in templates.
+     *
+     * Takes precedence over built-in helpers with the same name.
+     *
+     * Format: `^[a-zA-Z_$][a-zA-Z0-9_$]*$`
+     */
+    name: string;
+    /**
+     * The path to the Rhai script implementing the helper, relative to the root directory
+     * of the template.
+     *
+     * Either this or `command` must be specified
+     */
+    script?: string;
+    /**
+     * The executable implementing the helper followed by its arguments.
+     *
+     * The executable is resolved relative to the root directory of the template if it's a
+     * path, otherwise it's looked up in `PATH`.
+     *
+     * Either this or `script` must be specified
+     */
+    command?: string[];
+    /**
+     * The time in milliseconds the command may take before it's killed and rendering fails.
+     *
+     * Default: `10000`
+     */
+    timeout?: number;
+}
+
+export type EscapeMode = 'html' | 'json' | 'yaml' | 'toml' | 'xml' | 'shell' | 'none';
+
+export interface Conflicts {
+    /**
+     * The strategy to resolve conflicts with, can be overridden using `--on-conflict`.
+     *
+     * Files are ordered by their path in the template.
+     *
+     * Default: `error`
+     */
+    strategy?: ConflictStrategy;
+    /**
+     * Glob strings for the `priority` strategy, the file matching the earliest of them is used.
+     *
+     * The conflict isn't resolved if none or multiple files match the earliest matching glob
+     */
+    priority?: string[];
+}
+
+export type ConflictStrategy = 'error' | 'first-wins' | 'last-wins' | 'concatenate' | 'priority';
+
+export interface Hooks {
+    /**
+     * Run one after another in an empty temporary directory after the questions were answered,
+     * before anything is rendered. The template directory is passed in `ARCHITECT_TEMPLATE_DIR`.
+     *
+     * They receive the context as JSON on stdin and can write a JSON patch (RFC 6902) to stdout
+     * to change it. A failing fatal hook aborts the generation
+     */
+    preGenerate?: Hook[];
+    /**
+     * Run one after another in the target directory once the project was generated
+     */
+    postGenerate?: Hook[];
+}
+
+export interface Hook {
+    /**
+     * The program and its arguments.
+     *
+     * Every element is rendered as a Handlebars template. A program specified as a path is
+     * resolved relative to the working directory (the template directory for `preGenerate`
+     * hooks), otherwise it's looked up in `PATH`
+     */
+    command: string[];
+    /**
+     * The working directory of the command, relative to the target directory. Only
+     * `postGenerate` hooks can specify it, `preGenerate` hooks always run in a temporary
+     * directory.
+     *
+     * Default: the target directory
+     */
+    workingDir?: string;
+    /**
+     * A boolean expression (like for conditional files), the hook only runs if it's true
+     */
+    when?: string;
+    /**
+     * Whether a failure of the hook fails the generation, skipping all following hooks.
+     *
+     * Default: `true`
+     */
+    fatal?: boolean;
+}
+
+export interface FileMode {
+    /**
+     * A Glob string matching source files relative to the template directory.
+     *
+     * If multiple globs match a file, the last one is used
+     */
+    matcher: string;
+    /**
+     * The octal mode, e.g. `755`
+     */
+    mode: string;
+}
+
+export interface FileEncoding {
+    /**
+     * A Glob string matching source files relative to the template directory.
+     *
+     * If multiple globs match a file, the last one is used
+     */
+    matcher: string;
+    /**
+     * The WHATWG label of the encoding, e.g. `ISO-8859-1` or `Shift_JIS`
+     */
+    encoding: string;
+}
+
+export interface Delimiters {
+    /**
+     * A Glob string matching source files relative to the template directory.
+     *
+     * If multiple globs match a file, the last one is used
+     */
+    matcher: string;
+    /**
+     * The start of an expression, replacing `{{`, e.g. `<%` or `[[`
+     */
+    start: string;
+    /**
+     * The end of an expression, replacing `}}`, e.g. `%>` or `]]`
+     */
+    end: string;
+}
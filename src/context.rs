@@ -30,15 +30,20 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
+use std::fs::read_to_string;
 use std::io;
+use std::io::{Error, ErrorKind};
 use std::mem::transmute;
+use std::path::Path;
 
-use dialoguer::{Confirm, Input, MultiSelect, Select};
+use dialoguer::{Confirm, Input, MultiSelect, Password, Select};
 use handlebars::Context;
+use path_absolutize::Absolutize;
 use regex::Regex;
+use serde::Serialize;
 use serde_json::{to_value, Map, Value};
 
-use crate::config::{Config, Question, QuestionSpec};
+use crate::config::{Config, DataFile, DataFormat, Question, QuestionSpec};
 use crate::term::theme::WithFormat;
 use crate::utils::is_identifier;
 
@@ -52,12 +57,6 @@ impl UnsafeContext {
             _data: Value::Object(data),
         }
     }
-
-    pub(crate) fn empty() -> Self {
-        UnsafeContext {
-            _data: Value::Object(Map::new()),
-        }
-    }
 }
 
 impl From<UnsafeContext> for Context {
@@ -66,32 +65,163 @@ impl From<UnsafeContext> for Context {
     }
 }
 
-pub fn build_context(config: &Config) -> io::Result<Context> {
+/// Information about the template and the generation that is added to `__template__`
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateMetadata<'a> {
+    pub source: String,
+    pub commit: Option<String>,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub sub_template: Option<&'a str>,
+    pub architect_version: &'a str,
+    pub generated_at: String,
+    pub target_dir_name: String,
+}
+
+pub fn build_context(
+    config: &Config,
+    metadata: &TemplateMetadata,
+    template_dir: &Path,
+    previous_answers: Option<&Map<String, Value>>,
+) -> io::Result<Context> {
+    let mut template_json = match to_value(config)? {
+        Value::Object(map) => map,
+        _ => unreachable!(),
+    };
+
+    if let Value::Object(metadata_json) = to_value(metadata)? {
+        template_json.extend(metadata_json);
+    }
+
     let mut context_json = Map::new();
-    context_json.insert("__template__".to_string(), to_value(config)?);
+    context_json.insert("__template__".to_string(), Value::Object(template_json));
+
+    for data_file in &config.data {
+        let data = load_data_file(data_file, template_dir)?;
+        insert_into_context(&mut context_json, data_file.path.names(), data);
+    }
 
     for question in &config.questions {
-        let answer = ask(question)?;
+        let previous_answer =
+            previous_answers.and_then(|answers| answers.get(&question.path.names().join(".")));
+
+        let answer = ask(question, previous_answer)?;
         insert_into_context(&mut context_json, question.path.names(), answer);
     }
 
     Ok(UnsafeContext::new(context_json).into())
 }
 
-fn ask(question: &Question) -> io::Result<Value> {
+fn load_data_file(data_file: &DataFile, template_dir: &Path) -> io::Result<Value> {
+    let file_path = template_dir
+        .join(data_file.file)
+        .absolutize()?
+        .to_path_buf();
+
+    if !file_path.starts_with(template_dir) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!(
+                "Data file '{}' would leave template directory '{}'",
+                data_file.file,
+                template_dir.display()
+            ),
+        ));
+    }
+
+    let content = read_to_string(&file_path).map_err(|err| {
+        Error::new(
+            err.kind(),
+            format!("Failed to read data file '{}' ({})", data_file.file, err),
+        )
+    })?;
+
+    let result = match data_file.format {
+        DataFormat::Json => serde_json::from_str(&content).map_err(|err| err.to_string()),
+        DataFormat::Yaml => serde_yaml::from_str(&content).map_err(|err| err.to_string()),
+        DataFormat::Toml => toml::from_str(&content).map_err(|err| err.to_string()),
+    };
+
+    result.map_err(|err| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("Failed to parse data file '{}' ({})", data_file.file, err),
+        )
+    })
+}
+
+fn ask(question: &Question, previous_answer: Option<&Value>) -> io::Result<Value> {
+    // Previous answers take precedence over the configured default values
     match &question.spec {
-        QuestionSpec::Identifier { default } => ask_for_text(question, true, default),
-        QuestionSpec::Text { default } => ask_for_text(question, false, default),
-        QuestionSpec::Option { default } => ask_for_option(question, default),
+        QuestionSpec::Identifier { default } => ask_for_text(
+            question,
+            true,
+            &previous_text(previous_answer, |it| it.split('.').all(is_identifier))
+                .or_else(|| default.clone()),
+        ),
+        QuestionSpec::Text {
+            default,
+            secret: true,
+        } => ask_for_secret(question, default),
+        QuestionSpec::Text { default, .. } => ask_for_text(
+            question,
+            false,
+            &previous_text(previous_answer, |_| true).or_else(|| default.clone()),
+        ),
+        QuestionSpec::Option { default } => ask_for_option(
+            question,
+            &previous_answer.and_then(Value::as_bool).or(*default),
+        ),
         QuestionSpec::Selection {
             items,
             multi: multi_select,
             default,
-        } => ask_for_selection(question, items, *multi_select, default),
-        QuestionSpec::Custom { format, default } => ask_for_custom(question, *format, default),
+        } => {
+            let previous_selection = previous_selection(previous_answer, items, *multi_select);
+
+            ask_for_selection(
+                question,
+                items,
+                *multi_select,
+                previous_selection.as_deref().unwrap_or(default),
+            )
+        }
+        QuestionSpec::Custom { format, default } => ask_for_custom(
+            question,
+            format,
+            &previous_text(previous_answer, |it| {
+                Regex::new(format.trim()).is_ok_and(|regex| regex.is_match(it))
+            })
+            .or_else(|| default.clone()),
+        ),
     }
 }
 
+fn previous_text(
+    previous_answer: Option<&Value>,
+    is_valid: impl Fn(&str) -> bool,
+) -> Option<String> {
+    previous_answer
+        .and_then(Value::as_str)
+        .filter(|&it| is_valid(it))
+        .map(String::from)
+}
+
+fn previous_selection(
+    previous_answer: Option<&Value>,
+    items: &[&str],
+    multi_select: bool,
+) -> Option<Vec<String>> {
+    previous_answer
+        .and_then(Value::as_object)
+        .map(|selection| selection.keys().cloned().collect::<Vec<_>>())
+        .filter(|selection| {
+            (multi_select || selection.len() == 1)
+                && selection.iter().all(|item| items.contains(&item.as_str()))
+        })
+}
+
 fn ask_for_text(
     question: &Question,
     must_be_identifier: bool,
@@ -119,6 +249,19 @@ fn ask_for_text(
     Ok(Value::String(text_input.interact_text()?))
 }
 
+fn ask_for_secret(question: &Question, default: &Option<String>) -> io::Result<Value> {
+    let mut password_input = Password::with_theme(&crate::term::theme::INSTANCE);
+    password_input.with_prompt(question.prompt());
+    password_input.allow_empty_password(default.is_some());
+
+    let value = password_input.interact()?;
+
+    Ok(Value::String(match default {
+        Some(default) if value.is_empty() => default.clone(),
+        _ => value,
+    }))
+}
+
 fn ask_for_option(question: &Question, default: &Option<bool>) -> io::Result<Value> {
     let mut confirm_prompt = Confirm::with_theme(&crate::term::theme::INSTANCE);
     confirm_prompt.with_prompt(question.prompt());
@@ -147,7 +290,7 @@ fn ask_for_selection(
         MultiSelect::with_theme(&crate::term::theme::INSTANCE)
             .with_prompt(prompt)
             .items(items)
-            .defaults(&*defaults)
+            .defaults(&defaults)
             .interact()?
     } else {
         let mut select = Select::with_theme(&crate::term::theme::INSTANCE);
@@ -238,7 +381,12 @@ impl Question<'_> {
 
 #[cfg(test)]
 mod tests {
-    use serde_json::Number;
+    use std::fs;
+
+    use serde_json::{json, Number};
+    use tempfile::tempdir;
+
+    use crate::config::QuestionPath;
 
     use super::*;
 
@@ -297,6 +445,117 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_load_data_file() -> io::Result<()> {
+        let template_dir = tempdir()?;
+
+        fs::write(
+            template_dir.path().join("regions.json"),
+            r#"{ "eu": "https://eu.example.com" }"#,
+        )?;
+        fs::write(
+            template_dir.path().join("java.yaml"),
+            "versions:\n  - 11\n  - 17\n",
+        )?;
+        fs::write(
+            template_dir.path().join("tool.toml"),
+            "[tool]\nname = \"architect\"\n",
+        )?;
+        fs::write(template_dir.path().join("broken.json"), "{ not json")?;
+
+        let data_file = |file, format| DataFile {
+            path: QuestionPath::parse("data").unwrap(),
+            file,
+            format,
+        };
+
+        assert_eq!(
+            json!({ "eu": "https://eu.example.com" }),
+            load_data_file(
+                &data_file("regions.json", DataFormat::Json),
+                template_dir.path()
+            )?
+        );
+
+        assert_eq!(
+            json!({ "versions": [11, 17] }),
+            load_data_file(
+                &data_file("java.yaml", DataFormat::Yaml),
+                template_dir.path()
+            )?
+        );
+
+        assert_eq!(
+            json!({ "tool": { "name": "architect" } }),
+            load_data_file(
+                &data_file("tool.toml", DataFormat::Toml),
+                template_dir.path()
+            )?
+        );
+
+        assert!(load_data_file(
+            &data_file("broken.json", DataFormat::Json),
+            template_dir.path()
+        )
+        .is_err());
+
+        assert!(load_data_file(
+            &data_file("missing.json", DataFormat::Json),
+            template_dir.path()
+        )
+        .is_err());
+
+        assert!(load_data_file(
+            &data_file("../outside.json", DataFormat::Json),
+            template_dir.path()
+        )
+        .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_previous_answers() {
+        let previous_text_answer = json!("com.example");
+
+        assert_eq!(
+            Some("com.example".to_string()),
+            previous_text(Some(&previous_text_answer), |it| it
+                .split('.')
+                .all(is_identifier))
+        );
+
+        assert_eq!(
+            None,
+            previous_text(Some(&previous_text_answer), |it| it.len() > 20)
+        );
+
+        assert_eq!(None, previous_text(Some(&json!(true)), |_| true));
+        assert_eq!(None, previous_text(None, |_| true));
+
+        let items = ["jdbc", "kafka", "redis"];
+
+        assert_eq!(
+            Some(vec!["jdbc".to_string(), "redis".to_string()]),
+            previous_selection(Some(&json!({ "jdbc": true, "redis": true })), &items, true)
+        );
+
+        assert_eq!(
+            None,
+            previous_selection(Some(&json!({ "jdbc": true, "redis": true })), &items, false)
+        );
+
+        assert_eq!(
+            None,
+            previous_selection(Some(&json!({ "mongo": true })), &items, true)
+        );
+
+        assert_eq!(
+            Some(vec![]),
+            previous_selection(Some(&json!({})), &items, true)
+        );
+    }
+
     fn create_test_value() -> Map<String, Value> {
         let mut check_map = Map::new();
         check_map.insert(String::from("test"), Value::String(String::from("value")));
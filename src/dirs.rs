@@ -30,6 +30,7 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
+use std::env::var_os;
 use std::fs::{create_dir_all, metadata};
 use std::io;
 use std::io::Error;
@@ -101,10 +102,10 @@ pub fn create_target_dir(
     }
 }
 
-pub fn is_valid_target_dir(path: &Path) -> io::Result<bool> {
+pub fn is_valid_target_dir(path: &Path, allow_existing: bool) -> io::Result<bool> {
     if metadata(path).is_ok() {
         if path.is_dir() {
-            Ok(path.read_dir()?.next().is_none())
+            Ok(allow_existing || path.read_dir()?.next().is_none())
         } else {
             Ok(false)
         }
@@ -113,6 +114,14 @@ pub fn is_valid_target_dir(path: &Path) -> io::Result<bool> {
     }
 }
 
+/// A file in the directory where Architect keeps data between runs, e.g. remembered answers
+pub fn data_file(name: &str) -> Option<PathBuf> {
+    var_os("ARCHITECT_DATA_DIR")
+        .map(PathBuf::from)
+        .or_else(|| dirs::data_dir().map(|data_dir| data_dir.join("architect")))
+        .map(|data_dir| data_dir.join(name))
+}
+
 fn create_err(template_spec: &TemplateSpec) -> Result<PathBuf, Error> {
     Err(Error::new(
         ErrorKind::InvalidInput,
@@ -248,13 +257,18 @@ mod tests {
     fn test_is_valid_target_dir() -> io::Result<()> {
         let dir = tempdir()?;
 
-        assert!(is_valid_target_dir(dir.path())?);
+        assert!(is_valid_target_dir(dir.path(), false)?);
 
         let file_path = dir.path().join("random_file");
-        fs::write(file_path, "test")?;
+        fs::write(&file_path, "test")?;
 
-        assert!(!is_valid_target_dir(dir.path())?);
-        assert!(!is_valid_target_dir(&dir.path().join("../.tmp000000"))?);
+        assert!(!is_valid_target_dir(dir.path(), false)?);
+        assert!(is_valid_target_dir(dir.path(), true)?);
+        assert!(!is_valid_target_dir(&file_path, true)?);
+        assert!(!is_valid_target_dir(
+            &dir.path().join("../.tmp000000"),
+            false
+        )?);
 
         Ok(())
     }
@@ -270,6 +284,10 @@ mod tests {
         let tool_config = ToolConfig {
             template: None,
             verbose: true,
+            strict: false,
+            lint: false,
+            on_existing: None,
+            on_conflict: None,
             no_history: false,
             no_init: false,
             dry_run: false,
@@ -282,6 +300,10 @@ mod tests {
         let tool_config = ToolConfig {
             template: Some("auto-template.input"),
             verbose: true,
+            strict: false,
+            lint: false,
+            on_existing: None,
+            on_conflict: None,
             no_history: false,
             no_init: false,
             dry_run: false,
@@ -294,6 +316,10 @@ mod tests {
         let tool_config = ToolConfig {
             template: Some("simple-template.input"),
             verbose: true,
+            strict: false,
+            lint: false,
+            on_existing: None,
+            on_conflict: None,
             no_history: false,
             no_init: false,
             dry_run: false,
@@ -0,0 +1,201 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::fs::{copy, read};
+use std::io;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crossterm::style::Stylize;
+use dialoguer::Confirm;
+use similar::{ChangeTag, TextDiff};
+
+use crate::utils::OnExisting;
+
+/// Applies the policy for files that already exist in the target directory.
+///
+/// New content is always written to the output (staging) directory, existing files are only
+/// replaced once it is moved into place.
+pub struct ExistingFiles<'a> {
+    policy: Option<OnExisting>,
+    target_dir: &'a Path,
+    /// Render workers run in parallel, but only one of them may ask at a time
+    prompt_lock: Mutex<()>,
+}
+
+impl<'a> ExistingFiles<'a> {
+    pub fn new(policy: Option<OnExisting>, target_dir: &'a Path) -> Self {
+        ExistingFiles {
+            policy,
+            target_dir,
+            prompt_lock: Mutex::new(()),
+        }
+    }
+
+    /// Whether the content should be written for the target. Creates a backup of the existing
+    /// file in the output directory if required by the policy, which a file can override
+    pub fn should_write(
+        &self,
+        target: &Path,
+        output: &Path,
+        content: &[u8],
+        policy: Option<OnExisting>,
+    ) -> io::Result<bool> {
+        let existing = match read(target) {
+            Ok(existing) => existing,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(true),
+            Err(err) => return Err(err),
+        };
+
+        // Nothing would change anyway
+        if existing == content {
+            return Ok(false);
+        }
+
+        match policy.or(self.policy).unwrap_or(OnExisting::Overwrite) {
+            OnExisting::Skip => Ok(false),
+            OnExisting::Overwrite => Ok(true),
+            OnExisting::Backup => {
+                let backup = backup_path(target);
+                copy(target, output.with_file_name(backup.file_name().unwrap()))?;
+
+                Ok(true)
+            }
+            OnExisting::Prompt => self.prompt(target, &existing, content),
+        }
+    }
+
+    fn prompt(&self, target: &Path, existing: &[u8], content: &[u8]) -> io::Result<bool> {
+        let _lock = self.prompt_lock.lock().unwrap();
+
+        let rel_target = target.strip_prefix(self.target_dir).unwrap_or(target);
+        println!(
+            "{} already exists:",
+            format!("{}", rel_target.display()).yellow()
+        );
+
+        match (std::str::from_utf8(existing), std::str::from_utf8(content)) {
+            (Ok(existing), Ok(content)) => print_diff(existing, content),
+            _ => println!("{}", "Binary files differ".dim()),
+        }
+
+        Confirm::with_theme(&crate::term::theme::INSTANCE)
+            .with_prompt(format!("Overwrite {}?", rel_target.display()))
+            .default(false)
+            .interact()
+    }
+}
+
+fn print_diff(existing: &str, content: &str) {
+    let diff = TextDiff::from_lines(existing, content);
+
+    for (i, group) in diff.grouped_ops(3).iter().enumerate() {
+        if i > 0 {
+            println!("{}", "...".dim());
+        }
+
+        for op in group {
+            for change in diff.iter_changes(op) {
+                let line = change.to_string_lossy();
+                let line = line.trim_end_matches(['\r', '\n']);
+
+                match change.tag() {
+                    ChangeTag::Delete => println!("{}", format!("-{}", line).red()),
+                    ChangeTag::Insert => println!("{}", format!("+{}", line).green()),
+                    ChangeTag::Equal => println!(" {}", line),
+                }
+            }
+        }
+    }
+}
+
+/// The first of `<name>.bak`, `<name>.bak.1`, ... that doesn't exist yet
+fn backup_path(target: &Path) -> PathBuf {
+    let file_name = target.file_name().unwrap().to_string_lossy();
+
+    (0..)
+        .map(|number| {
+            target.with_file_name(if number == 0 {
+                format!("{}.bak", file_name)
+            } else {
+                format!("{}.bak.{}", file_name, number)
+            })
+        })
+        .find(|it| !it.exists())
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, read_to_string, write};
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_should_write() -> io::Result<()> {
+        let base_dir = tempdir()?;
+        let target_dir = base_dir.path().join("target");
+        let output_dir = base_dir.path().join("output");
+        create_dir_all(&target_dir)?;
+        create_dir_all(&output_dir)?;
+
+        let target = target_dir.join("file.txt");
+        let output = output_dir.join("file.txt");
+        write(&target, "existing")?;
+        write(target_dir.join("file.txt.bak"), "old backup")?;
+
+        let new_file = target_dir.join("new.txt");
+
+        let skip = ExistingFiles::new(Some(OnExisting::Skip), &target_dir);
+        assert!(skip.should_write(&new_file, &output, b"new", None)?);
+        assert!(!skip.should_write(&target, &output, b"new", None)?);
+
+        let overwrite = ExistingFiles::new(Some(OnExisting::Overwrite), &target_dir);
+        assert!(overwrite.should_write(&target, &output, b"new", None)?);
+        assert!(!overwrite.should_write(&target, &output, b"existing", None)?);
+
+        assert!(skip.should_write(&target, &output, b"new", Some(OnExisting::Overwrite))?);
+        assert!(!overwrite.should_write(&target, &output, b"new", Some(OnExisting::Skip))?);
+
+        let backup = ExistingFiles::new(Some(OnExisting::Backup), &target_dir);
+        assert!(backup.should_write(&target, &output, b"new", None)?);
+        assert_eq!(
+            "existing",
+            read_to_string(output_dir.join("file.txt.bak.1"))?
+        );
+
+        Ok(())
+    }
+}
@@ -66,6 +66,52 @@ pub fn is_git_repo(path: &Path, tool_config: &ToolConfig) -> bool {
     result
 }
 
+pub fn read_git_config_value(name: &str) -> Option<String> {
+    git2::Config::open_default()
+        .and_then(|mut git_config| git_config.snapshot())
+        .and_then(|snapshot| snapshot.get_string(name))
+        .ok()
+}
+
+pub struct GitInfo {
+    pub commit: String,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+}
+
+/// Reads the commit checked out in the repository at `path`, the branch if `HEAD` points
+/// to one, and a tag pointing to the commit
+pub fn read_git_info(path: &Path) -> Option<GitInfo> {
+    let repo = Repository::open(path).ok()?;
+    let head = repo.head().ok()?;
+    let commit = head.peel_to_commit().ok()?;
+
+    let branch = if head.is_branch() {
+        head.shorthand().map(String::from)
+    } else {
+        None
+    };
+
+    let tag = repo.tag_names(None).ok().and_then(|tag_names| {
+        tag_names
+            .iter()
+            .flatten()
+            .find(|&tag_name| {
+                repo.revparse_single(&format!("refs/tags/{}", tag_name))
+                    .and_then(|it| it.peel_to_commit())
+                    .map(|it| it.id() == commit.id())
+                    .unwrap_or(false)
+            })
+            .map(String::from)
+    });
+
+    Some(GitInfo {
+        commit: commit.id().to_string(),
+        branch,
+        tag,
+    })
+}
+
 pub fn fetch(spec: &TemplateSpec, target: &Path, options: &FetchOptions) -> ArchResult<()> {
     if options.tool_config.verbose {
         println!("{}", "Using embedded Git".dim());
@@ -206,21 +252,20 @@ where
 
             return if r.is_err() {
                 let mut final_username = username.map(|it| it.to_string());
-                let final_password: Option<String>;
 
                 if final_username.is_none() {
                     final_username = Some(read_username()?);
                 }
 
-                match Password::new().with_prompt("Password").interact() {
-                    Ok(password) => final_password = Some(password),
+                let final_password = match Password::new().with_prompt("Password").interact() {
+                    Ok(password) => Some(password),
                     Err(err) => {
                         return Err(git2::Error::from_str(&format!(
                             "failed to enter password ({})",
                             err
                         )));
                     }
-                }
+                };
 
                 git2::Cred::userpass_plaintext(&final_username.unwrap(), &final_password.unwrap())
             } else {
@@ -391,6 +436,10 @@ mod tests {
     const TOOL_CONFIG: ToolConfig<'_> = ToolConfig {
         template: None,
         verbose: true,
+        strict: false,
+        lint: false,
+        on_existing: None,
+        on_conflict: None,
         no_history: false,
         no_init: false,
         dry_run: false,
@@ -405,6 +454,31 @@ mod tests {
         assert!(!is_git_repo(&RESOURCES_DIR, &TOOL_CONFIG));
     }
 
+    #[test]
+    fn test_read_git_info() -> ArchResult<()> {
+        let tempdir = tempfile::tempdir()?;
+        assert!(read_git_info(tempdir.path()).is_none());
+
+        let repo = Repository::init(tempdir.path())?;
+        let signature = git2::Signature::now("Some dude!", "dude@example.com")?;
+        let tree = repo.find_tree(repo.index()?.write_tree()?)?;
+        let commit_id = repo.commit(Some("HEAD"), &signature, &signature, "Initial", &tree, &[])?;
+
+        let git_info = read_git_info(tempdir.path()).unwrap();
+        assert_eq!(commit_id.to_string(), git_info.commit);
+        assert!(git_info.branch.is_some());
+        assert_eq!(None, git_info.tag);
+
+        repo.tag_lightweight("v1.0.0", &repo.find_object(commit_id, None)?, false)?;
+        repo.set_head_detached(commit_id)?;
+
+        let git_info = read_git_info(tempdir.path()).unwrap();
+        assert_eq!(None, git_info.branch);
+        assert_eq!(Some("v1.0.0".into()), git_info.tag);
+
+        Ok(())
+    }
+
     //noinspection DuplicatedCode
     #[test]
     fn test_fetch() {
@@ -55,7 +55,7 @@ pub fn fetch(spec: &TemplateSpec, target: &Path, options: &FetchOptions) -> Arch
     command.arg(target.as_os_str());
 
     if let Some(branch) = options.branch {
-        command.args(&["--branch", branch]);
+        command.args(["--branch", branch]);
     }
 
     let mut child = command.spawn()?;
@@ -83,6 +83,10 @@ mod tests {
     const TOOL_CONFIG: ToolConfig<'_> = ToolConfig {
         template: None,
         verbose: true,
+        strict: false,
+        lint: false,
+        on_existing: None,
+        on_conflict: None,
         no_history: false,
         no_init: false,
         dry_run: false,
@@ -30,17 +30,22 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
+use std::fs::{copy, create_dir_all, read_link};
+use std::io;
 use std::path::Path;
 
 use anyhow::Context;
 use crossterm::style::Stylize;
 use dircpy::copy_dir;
 use git2::Repository;
+use walkdir::WalkDir;
 
 use crate::fetch::embedded::is_git_repo;
 use crate::spec::TemplateSpec;
 use crate::utils::errors::ArchResult;
-use crate::utils::ToolConfig;
+use crate::utils::{symlink, ToolConfig};
+
+pub use embedded::{read_git_config_value, read_git_info};
 
 mod embedded;
 mod installed;
@@ -67,7 +72,7 @@ impl<'spec> TemplateSpec<'spec> {
                     println!("{}", "Copying local directory".dim());
                 }
 
-                copy_dir(local_path, into).context("Failed to copy local directory")
+                copy_local_dir(local_path, into).context("Failed to copy local directory")
             } else {
                 panic!()
             }
@@ -101,6 +106,25 @@ pub fn copy_git_directory(
     Ok(())
 }
 
+/// Copies a local template directory, keeping symbolic links instead of copying their targets
+fn copy_local_dir(source_dir: &Path, into: &Path) -> io::Result<()> {
+    // The root is skipped, it's followed even if it's a link
+    for entry in WalkDir::new(source_dir).min_depth(1) {
+        let entry = entry?;
+        let target = into.join(entry.path().strip_prefix(source_dir).unwrap());
+
+        if entry.path_is_symlink() {
+            symlink(&read_link(entry.path())?, &target)?;
+        } else if entry.file_type().is_dir() {
+            create_dir_all(&target)?;
+        } else {
+            copy(entry.path(), &target)?;
+        }
+    }
+
+    Ok(())
+}
+
 fn remove_remotes(dir: &Path) {
     match Repository::open(dir) {
         Ok(repo) => {
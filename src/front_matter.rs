@@ -0,0 +1,230 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use serde::Deserialize;
+use serde_yaml::Value;
+
+use crate::condition::Expression;
+use crate::utils::{parse_mode, OnExisting};
+
+/// The comment on the opening line that marks a YAML block as front matter, other blocks like the
+/// ones in YAML documents or Markdown pages are left as they are
+const MARKER: &str = "architect";
+
+/// Options a template file declares for itself in a YAML block at its start, opened by a
+/// `--- # architect` line and closed by a `---` line
+#[derive(Debug)]
+pub struct FrontMatter {
+    /// The target path relative to the target directory, rendered as a Handlebars template
+    pub target: Option<String>,
+    /// The file is only created if this is true
+    pub when: Option<Expression>,
+    pub file_options: FileOptions,
+}
+
+/// The part of the front matter that is applied when the file is written
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct FileOptions {
+    pub mode: Option<u32>,
+    pub overwrite: Option<OnExisting>,
+    /// The number of lines of the block including its delimiters, which are stripped before
+    /// rendering
+    pub lines: usize,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawFrontMatter {
+    target: Option<String>,
+    when: Option<String>,
+    mode: Option<Value>,
+    overwrite: Option<String>,
+}
+
+/// Parses the front matter at the start of a template, if it has one
+pub fn parse_front_matter(content: &str) -> Result<Option<FrontMatter>, String> {
+    let mut lines = content.split_inclusive('\n');
+
+    let first_line = match lines.next() {
+        Some(line) if is_opening_line(line) => line,
+        _ => return Ok(None),
+    };
+
+    let mut block_end = first_line.len();
+    let mut line_count = 1;
+    let mut closed = false;
+
+    for line in lines {
+        line_count += 1;
+
+        if line.trim_end() == "---" {
+            closed = true;
+            break;
+        }
+
+        block_end += line.len();
+    }
+
+    if !closed {
+        return Err("It isn't closed by a '---' line".into());
+    }
+
+    let raw: RawFrontMatter = match serde_yaml::from_str(&content[first_line.len()..block_end]) {
+        Ok(raw) => raw,
+        // An empty block is null in YAML
+        Err(_) if content[first_line.len()..block_end].trim().is_empty() => Default::default(),
+        Err(err) => return Err(err.to_string()),
+    };
+
+    let when = raw
+        .when
+        .map(|when| Expression::parse(&when))
+        .transpose()
+        .map_err(|err| format!("Invalid condition ({})", err))?;
+
+    let mode = raw
+        .mode
+        .map(|mode| {
+            let mode = match mode {
+                Value::String(mode) => mode,
+                Value::Number(mode) => mode.to_string(),
+                _ => String::new(),
+            };
+
+            parse_mode(&mode).ok_or_else(|| {
+                format!(
+                    "Invalid mode '{}' (expected an octal number like 755)",
+                    mode
+                )
+            })
+        })
+        .transpose()?;
+
+    let overwrite = raw
+        .overwrite
+        .map(|overwrite| {
+            OnExisting::parse(overwrite.trim()).ok_or_else(|| {
+                format!(
+                    "Unknown overwrite policy '{}' (expected {})",
+                    overwrite,
+                    OnExisting::VALUES.join(", ")
+                )
+            })
+        })
+        .transpose()?;
+
+    Ok(Some(FrontMatter {
+        target: raw.target.map(|it| it.trim().to_string()),
+        when,
+        file_options: FileOptions {
+            mode,
+            overwrite,
+            lines: line_count,
+        },
+    }))
+}
+
+/// Removes the lines of a front matter from the start of the template
+pub fn strip_front_matter(content: &str, lines: usize) -> &str {
+    let length: usize = content
+        .split_inclusive('\n')
+        .take(lines)
+        .map(|line| line.len())
+        .sum();
+
+    &content[length..]
+}
+
+fn is_opening_line(line: &str) -> bool {
+    line.strip_prefix("---")
+        .and_then(|rest| rest.trim().strip_prefix('#'))
+        .is_some_and(|comment| comment.trim() == MARKER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_front_matter() {
+        let content = "--- # architect\ntarget: src/{{ name }}.rs\nwhen: useRust && name != 'lib'\nmode: 755\noverwrite: skip\n---\nfn main() {}\n";
+        let front_matter = parse_front_matter(content).unwrap().unwrap();
+
+        assert_eq!(Some("src/{{ name }}.rs"), front_matter.target.as_deref());
+        assert!(front_matter.when.is_some());
+        assert_eq!(
+            FileOptions {
+                mode: Some(0o755),
+                overwrite: Some(OnExisting::Skip),
+                lines: 6,
+            },
+            front_matter.file_options
+        );
+        assert_eq!("fn main() {}\n", strip_front_matter(content, 6));
+
+        let content = "---   #   architect\r\nmode: '600'\r\n---\r\n";
+        let front_matter = parse_front_matter(content).unwrap().unwrap();
+        assert_eq!(Some(0o600), front_matter.file_options.mode);
+        assert_eq!("", strip_front_matter(content, 3));
+
+        let front_matter = parse_front_matter("--- # architect\n---\n")
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            FileOptions::default(),
+            FileOptions {
+                lines: 0,
+                ..front_matter.file_options
+            }
+        );
+
+        // Blocks without the marker are content, e.g. in YAML documents and Markdown pages
+        assert!(parse_front_matter("no front matter").unwrap().is_none());
+        assert!(
+            parse_front_matter("---\ntarget: es2015\nmodule: commonjs\n---\n")
+                .unwrap()
+                .is_none()
+        );
+        assert!(parse_front_matter("--- # comment\nmode: 755\n---\n")
+            .unwrap()
+            .is_none());
+
+        assert!(parse_front_matter("--- # architect\ntarget: a.txt\n").is_err());
+        assert!(parse_front_matter("--- # architect\n- a\n---\n").is_err());
+        assert!(
+            parse_front_matter("--- # architect\ntarget: a.txt\ntraget: b.txt\n---\n").is_err()
+        );
+        assert!(parse_front_matter("--- # architect\nwhen: a ==\n---\n").is_err());
+        assert!(parse_front_matter("--- # architect\nmode: rwx\n---\n").is_err());
+        assert!(parse_front_matter("--- # architect\noverwrite: never\n---\n").is_err());
+    }
+}
@@ -0,0 +1,235 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::thread;
+use std::time::Duration;
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+};
+use serde_json::{Map, Value as Json};
+use wait_timeout::ChildExt;
+
+/// A helper that is implemented by an external executable.
+///
+/// The executable receives the params and hash of the helper as JSON on stdin, i.e.
+/// `{"params": [...], "hash": {...}}`, and writes the rendered string to stdout
+pub struct CommandHelper {
+    name: String,
+    program: PathBuf,
+    args: Vec<String>,
+    working_dir: PathBuf,
+    timeout: Duration,
+}
+
+impl CommandHelper {
+    pub fn new(
+        name: &str,
+        program: PathBuf,
+        args: Vec<String>,
+        working_dir: PathBuf,
+        timeout: Duration,
+    ) -> Self {
+        CommandHelper {
+            name: name.into(),
+            program,
+            args,
+            working_dir,
+            timeout,
+        }
+    }
+
+    fn run(&self, input: &Json) -> Result<String, String> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|err| format!("Failed to start {} ({})", self.program.display(), err))?;
+
+        // stdin, stdout, and stderr are handled in separate threads, so a helper that
+        // writes a lot of output before reading its input cannot block
+        let mut stdin = child.stdin.take().unwrap();
+        let input = serde_json::to_vec(input).unwrap();
+        thread::spawn(move || stdin.write_all(&input));
+
+        let mut stdout = child.stdout.take().unwrap();
+        let stdout_reader = thread::spawn(move || {
+            let mut buf = Vec::new();
+            stdout.read_to_end(&mut buf).map(|_| buf)
+        });
+
+        let mut stderr = child.stderr.take().unwrap();
+        let stderr_reader = thread::spawn(move || {
+            let mut buf = String::new();
+            stderr.read_to_string(&mut buf).map(|_| buf)
+        });
+
+        let status = match child
+            .wait_timeout(self.timeout)
+            .map_err(|err| format!("Failed to wait for {} ({})", self.program.display(), err))?
+        {
+            Some(status) => status,
+            None => {
+                let _ = child.kill();
+                let _ = child.wait();
+
+                return Err(format!(
+                    "{} timed out after {} ms",
+                    self.program.display(),
+                    self.timeout.as_millis()
+                ));
+            }
+        };
+
+        let stdout = stdout_reader.join().unwrap().unwrap_or_default();
+        let stderr = stderr_reader.join().unwrap().unwrap_or_default();
+
+        if !status.success() {
+            return Err(format!(
+                "{} failed with {}: {}",
+                self.program.display(),
+                status,
+                stderr.trim()
+            ));
+        }
+
+        let mut output = String::from_utf8(stdout)
+            .map_err(|_| format!("{} didn't write valid UTF-8", self.program.display()))?;
+
+        // Most tools terminate their output with a line break, which is never wanted here
+        if output.ends_with('\n') {
+            output.pop();
+            if output.ends_with('\r') {
+                output.pop();
+            }
+        }
+
+        Ok(output)
+    }
+}
+
+impl HelperDef for CommandHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let params = h
+            .params()
+            .iter()
+            .map(|it| it.value().clone())
+            .collect::<Vec<_>>();
+
+        let hash = h
+            .hash()
+            .iter()
+            .map(|(&key, value)| (key.to_string(), value.value().clone()))
+            .collect::<Map<_, _>>();
+
+        let mut input = Map::new();
+        input.insert("params".into(), Json::Array(params));
+        input.insert("hash".into(), Json::Object(hash));
+
+        let output = self
+            .run(&Json::Object(input))
+            .map_err(|err| RenderError::new(format!("Helper \"{}\" failed: {}", self.name, err)))?;
+
+        out.write(&output)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn sh_helper(script: &str, timeout: Duration) -> CommandHelper {
+        CommandHelper::new(
+            "test",
+            "sh".into(),
+            vec!["-c".into(), script.into()],
+            std::env::temp_dir(),
+            timeout,
+        )
+    }
+
+    #[test]
+    fn test_command_helper() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("echo", Box::new(sh_helper("cat", Duration::from_secs(5))));
+        handlebars.register_helper(
+            "fail",
+            Box::new(sh_helper(
+                "echo 'no index' >&2; exit 3",
+                Duration::from_secs(5),
+            )),
+        );
+        handlebars.register_helper(
+            "slow",
+            Box::new(sh_helper("sleep 5", Duration::from_millis(100))),
+        );
+
+        assert_eq!(
+            r#"{"hash":{"scope":"compile"},"params":["lib",2]}"#,
+            handlebars
+                .render_template(
+                    r#"{{{echo name 2 scope="compile"}}}"#,
+                    &json!({ "name": "lib" })
+                )
+                .unwrap()
+        );
+
+        let fail_error = handlebars
+            .render_template("{{fail}}", &json!({}))
+            .unwrap_err()
+            .to_string();
+        assert!(fail_error.contains("no index"), "{}", fail_error);
+
+        let slow_error = handlebars
+            .render_template("{{slow}}", &json!({}))
+            .unwrap_err()
+            .to_string();
+        assert!(slow_error.contains("timed out"), "{}", slow_error);
+    }
+}
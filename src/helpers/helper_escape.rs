@@ -0,0 +1,235 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::cell::Cell;
+
+use handlebars::{
+    html_escape, no_escape, Context, Handlebars, Helper, HelperDef, HelperResult, Output,
+    RenderContext, RenderError,
+};
+use serde_json::Value as Json;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EscapeMode {
+    Html,
+    Json,
+    Yaml,
+    Toml,
+    Xml,
+    Shell,
+    None,
+}
+
+impl EscapeMode {
+    pub fn parse(name: &str) -> Option<EscapeMode> {
+        match name.trim().to_lowercase().as_str() {
+            "html" => Some(EscapeMode::Html),
+            "json" => Some(EscapeMode::Json),
+            "yaml" => Some(EscapeMode::Yaml),
+            "toml" => Some(EscapeMode::Toml),
+            "xml" => Some(EscapeMode::Xml),
+            "shell" => Some(EscapeMode::Shell),
+            "none" => Some(EscapeMode::None),
+            _ => None,
+        }
+    }
+
+    pub fn escape(self, data: &str) -> String {
+        match self {
+            EscapeMode::Html => html_escape(data),
+            EscapeMode::Json | EscapeMode::Yaml | EscapeMode::Toml => escape_double_quoted(data),
+            EscapeMode::Xml => escape_xml(data),
+            EscapeMode::Shell => escape_single_quoted_shell(data),
+            EscapeMode::None => no_escape(data),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT_ESCAPE_MODE: Cell<EscapeMode> = const { Cell::new(EscapeMode::Html) };
+}
+
+/// The escape function registered on all Handlebars instances, it escapes according to the
+/// mode set using [with_escape_mode] for the current thread
+pub fn escape_with_current_mode(data: &str) -> String {
+    CURRENT_ESCAPE_MODE.with(|it| it.get()).escape(data)
+}
+
+/// Runs `f` with `mode` as escape mode for the current thread, because the Handlebars
+/// instance is shared by all render workers and its escape function cannot be changed per file
+pub fn with_escape_mode<T>(mode: EscapeMode, f: impl FnOnce() -> T) -> T {
+    let previous = CURRENT_ESCAPE_MODE.with(|it| it.replace(mode));
+    let result = f();
+    CURRENT_ESCAPE_MODE.with(|it| it.set(previous));
+
+    result
+}
+
+/// Escapes the content of double-quoted JSON, YAML, and TOML strings
+fn escape_double_quoted(data: &str) -> String {
+    let mut result = String::with_capacity(data.len());
+
+    for c in data.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            '\n' => result.push_str("\\n"),
+            '\r' => result.push_str("\\r"),
+            '\t' => result.push_str("\\t"),
+            '\u{8}' => result.push_str("\\b"),
+            '\u{c}' => result.push_str("\\f"),
+            _ if c.is_control() && (c as u32) < 0x80 => {
+                result.push_str(&format!("\\u{:04x}", c as u32))
+            }
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+fn escape_xml(data: &str) -> String {
+    let mut result = String::with_capacity(data.len());
+
+    for c in data.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            '\'' => result.push_str("&apos;"),
+            _ => result.push(c),
+        }
+    }
+
+    result
+}
+
+/// Escapes the content of single-quoted POSIX shell strings, which don't support any escape
+/// sequences, so single quotes end the string, add an escaped quote, and start a new one
+fn escape_single_quoted_shell(data: &str) -> String {
+    data.replace('\'', "'\\''")
+}
+
+/// `{{escape_json value}}` and the like escape a value for use in a quoted string of the
+/// respective format, regardless of the escape mode of the file
+#[derive(Clone, Copy)]
+pub struct EscapeHelper(pub EscapeMode, pub &'static str);
+
+impl HelperDef for EscapeHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let param = h.param(0).ok_or_else(|| {
+            RenderError::new(format!("Param not found for helper \"{}\"", self.1))
+        })?;
+
+        let escaped = match param.value() {
+            Json::String(value) => self.0.escape(value),
+            Json::Null => String::new(),
+            value => self.0.escape(&value.to_string()),
+        };
+
+        out.write(&escaped)?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        let input = "Say \"hi\": it's <fun> & \\ \n\tdone\u{7f}";
+
+        assert_eq!(
+            r#"Say \"hi\": it's <fun> & \\ \n\tdone\u007f"#,
+            EscapeMode::Json.escape(input)
+        );
+        assert_eq!(
+            EscapeMode::Json.escape(input),
+            EscapeMode::Yaml.escape(input)
+        );
+        assert_eq!(
+            EscapeMode::Json.escape(input),
+            EscapeMode::Toml.escape(input)
+        );
+        assert_eq!(
+            "Say &quot;hi&quot;: it&apos;s &lt;fun&gt; &amp; \\ \n\tdone\u{7f}",
+            EscapeMode::Xml.escape(input)
+        );
+        assert_eq!(
+            "Say \"hi\": it'\\''s <fun> & \\ \n\tdone\u{7f}",
+            EscapeMode::Shell.escape(input)
+        );
+        assert_eq!(input, EscapeMode::None.escape(input));
+    }
+
+    #[test]
+    fn test_escape_modes() {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_escape_fn(escape_with_current_mode);
+        handlebars.register_helper(
+            "escape_shell",
+            Box::new(EscapeHelper(EscapeMode::Shell, "escape_shell")),
+        );
+
+        let data = json!({ "description": "A \"quoted\" <value>" });
+        let render = |template: &str| handlebars.render_template(template, &data).unwrap();
+
+        assert_eq!(
+            "A &quot;quoted&quot; &lt;value&gt;",
+            render("{{description}}")
+        );
+        assert_eq!(
+            r#"A \"quoted\" <value>"#,
+            with_escape_mode(EscapeMode::Json, || render("{{description}}"))
+        );
+        assert_eq!(
+            "A \"quoted\" <value>",
+            with_escape_mode(EscapeMode::Json, || render("{{escape_shell description}}"))
+        );
+        assert_eq!(
+            "A &quot;quoted&quot; &lt;value&gt;",
+            render("{{description}}")
+        );
+    }
+}
@@ -0,0 +1,108 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+};
+use serde_json::Value as Json;
+
+/// `{{join_path "src" dir "Main.java"}}` joins its params using `/`, independent of the OS
+#[derive(Clone, Copy)]
+pub struct JoinPathHelper;
+
+pub const JOIN_PATH_HELPER: JoinPathHelper = JoinPathHelper {};
+
+impl HelperDef for JoinPathHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let parts = h
+            .params()
+            .iter()
+            .map(|param| match param.value() {
+                Json::String(value) => Ok(value.clone()),
+                Json::Number(value) => Ok(value.to_string()),
+                _ => Err(RenderError::new(
+                    "Param value not a String or Number for helper \"join_path\"",
+                )),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        out.write(&join_path_helper_impl(&parts))?;
+
+        Ok(())
+    }
+}
+
+fn join_path_helper_impl(parts: &[String]) -> String {
+    let absolute = parts
+        .iter()
+        .find(|it| !it.is_empty())
+        .map(|it| it.starts_with('/'))
+        .unwrap_or(false);
+
+    let joined = parts
+        .iter()
+        .flat_map(|it| it.split('/'))
+        .filter(|it| !it.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+
+    if absolute {
+        format!("/{}", joined)
+    } else {
+        joined
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_join_path_helper_impl() {
+        let join = |parts: &[&str]| {
+            join_path_helper_impl(&parts.iter().map(|&it| it.into()).collect::<Vec<_>>())
+        };
+
+        assert_eq!("src/main/Main.java", join(&["src", "main", "Main.java"]));
+        assert_eq!("src/main/Main.java", join(&["src/", "/main/", "Main.java"]));
+        assert_eq!("src/Main.java", join(&["src", "", "Main.java"]));
+        assert_eq!("/opt/app", join(&["/opt", "app"]));
+        assert_eq!("", join(&[]));
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::env::var;
+use std::str::FromStr;
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, TimeZone, Utc};
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+};
+use lazy_static::lazy_static;
+use serde_json::Value as Json;
+
+lazy_static! {
+    static ref SOURCE_DATE_EPOCH: Option<DateTime<Utc>> = var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|raw| i64::from_str(raw.trim()).ok())
+        .and_then(|epoch| Utc.timestamp_opt(epoch, 0).single());
+}
+
+/// `{{now}}` renders the current time in UTC, either as RFC 3339 or using the `strftime`
+/// format passed as param, e.g. `{{now "%Y"}}`.
+///
+/// The time is taken from `SOURCE_DATE_EPOCH` if it's set
+#[derive(Clone, Copy)]
+pub struct NowHelper;
+
+pub const NOW_HELPER: NowHelper = NowHelper {};
+
+impl HelperDef for NowHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        _: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let format = match h.param(0).map(|it| it.value()) {
+            Some(Json::String(format)) => Some(format.as_str()),
+            Some(_) => {
+                return Err(RenderError::new(
+                    "Param value not a String for helper \"now\"",
+                ))
+            }
+            None => None,
+        };
+
+        out.write(&now_helper_impl(now(), format)?)?;
+
+        Ok(())
+    }
+}
+
+/// The current time, or the time set using `SOURCE_DATE_EPOCH`
+pub fn now() -> DateTime<Utc> {
+    SOURCE_DATE_EPOCH.unwrap_or_else(Utc::now)
+}
+
+fn now_helper_impl(now: DateTime<Utc>, format: Option<&str>) -> Result<String, RenderError> {
+    match format {
+        Some(format) => {
+            let items = StrftimeItems::new(format).collect::<Vec<_>>();
+
+            // Formatting would panic later on otherwise
+            if items.iter().any(|it| matches!(it, Item::Error)) {
+                return Err(RenderError::new(format!(
+                    "Invalid format '{}' for helper \"now\"",
+                    format
+                )));
+            }
+
+            Ok(now.format_with_items(items.into_iter()).to_string())
+        }
+        None => Ok(now.to_rfc3339()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_now_helper_impl() {
+        let now = Utc.timestamp_opt(1_638_316_800, 0).unwrap();
+
+        assert_eq!(
+            "2021-12-01T00:00:00+00:00",
+            now_helper_impl(now, None).unwrap()
+        );
+        assert_eq!("2021", now_helper_impl(now, Some("%Y")).unwrap());
+        assert_eq!(
+            "01.12.2021 00:00",
+            now_helper_impl(now, Some("%d.%m.%Y %H:%M")).unwrap()
+        );
+        assert!(now_helper_impl(now, Some("%Q")).is_err());
+    }
+}
@@ -78,7 +78,7 @@ mod tests {
     #[test]
     fn test_package_helper_impl() {
         assert_eq!(
-            vec!["io", "v47", "test"].join(&String::from(std::path::MAIN_SEPARATOR)),
+            ["io", "v47", "test"].join(&String::from(std::path::MAIN_SEPARATOR)),
             package_helper_impl("io.v47.test")
         );
     }
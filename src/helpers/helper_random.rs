@@ -0,0 +1,317 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::collections::HashMap;
+use std::env::var;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use handlebars::{
+    Context, Handlebars, Helper, HelperDef, HelperResult, Output, RenderContext, RenderError,
+};
+use lazy_static::lazy_static;
+use rand::distributions::Alphanumeric;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde_json::Value as Json;
+use uuid::{Builder, Variant, Version};
+
+lazy_static! {
+    static ref SEED: Option<u64> = var("ARCHITECT_SEED")
+        .ok()
+        .and_then(|raw| u64::from_str(raw.trim()).ok());
+}
+
+/// Provides the random number generators for the random helpers.
+///
+/// Without a seed the generators use the entropy of the OS. With a seed (`ARCHITECT_SEED`)
+/// every call gets a generator derived from the seed, the file that is rendered, and the
+/// number of random values already used in that file. This keeps the results stable even
+/// though files are rendered in parallel
+pub struct RandomSource {
+    seed: Option<u64>,
+    counters: Mutex<HashMap<String, u64>>,
+}
+
+impl RandomSource {
+    pub fn new() -> Arc<Self> {
+        Self::with_seed(*SEED)
+    }
+
+    pub fn with_seed(seed: Option<u64>) -> Arc<Self> {
+        Arc::new(RandomSource {
+            seed,
+            counters: Mutex::new(HashMap::new()),
+        })
+    }
+
+    fn rng(&self, ctx: &Context) -> StdRng {
+        let seed = match self.seed {
+            Some(seed) => seed,
+            None => return StdRng::from_entropy(),
+        };
+
+        let file_key = file_key(ctx);
+
+        let mut counters = self.counters.lock().unwrap();
+        let counter = counters.entry(file_key.clone()).or_insert(0);
+        *counter += 1;
+
+        let mut hash = fnv1a(FNV_OFFSET_BASIS, &seed.to_le_bytes());
+        hash = fnv1a(hash, file_key.as_bytes());
+        hash = fnv1a(hash, &counter.to_le_bytes());
+
+        StdRng::seed_from_u64(hash)
+    }
+}
+
+/// The path of the rendered file relative to the template, empty when rendering names
+fn file_key(ctx: &Context) -> String {
+    let file = &ctx.data()["__template__"]["file"];
+
+    match (file["rootDir"].as_str(), file["sourcePath"].as_str()) {
+        (Some(root_dir), Some(source_path)) => Path::new(source_path)
+            .strip_prefix(root_dir)
+            .unwrap_or_else(|_| Path::new(source_path))
+            .iter()
+            .map(|it| it.to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/"),
+        _ => String::new(),
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+// FNV-1a is used because it's stable across platforms and Rust versions, unlike the
+// hashers in the standard library
+fn fnv1a(mut hash: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    hash
+}
+
+/// `{{uuid}}` creates a random (v4) UUID
+pub struct UuidHelper(pub Arc<RandomSource>);
+
+impl HelperDef for UuidHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        _: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let bytes: [u8; 16] = self.0.rng(ctx).gen();
+
+        let uuid = Builder::from_bytes(bytes)
+            .set_variant(Variant::RFC4122)
+            .set_version(Version::Random)
+            .build();
+
+        out.write(&uuid.to_hyphenated().to_string())?;
+
+        Ok(())
+    }
+}
+
+/// `{{random min max}}` creates a random integer between `min` and `max` (both inclusive)
+pub struct RandomHelper(pub Arc<RandomSource>);
+
+impl HelperDef for RandomHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let min = integer_param(h, 0, "random")?;
+        let max = integer_param(h, 1, "random")?;
+
+        if min > max {
+            return Err(RenderError::new(format!(
+                "Min value {} is greater than max value {} for helper \"random\"",
+                min, max
+            )));
+        }
+
+        out.write(&self.0.rng(ctx).gen_range(min..=max).to_string())?;
+
+        Ok(())
+    }
+}
+
+/// `{{random_string length}}` creates a random alphanumeric string, the characters to use
+/// can be changed using the hash parameter `chars`
+pub struct RandomStringHelper(pub Arc<RandomSource>);
+
+impl HelperDef for RandomStringHelper {
+    fn call<'reg: 'rc, 'rc>(
+        &self,
+        h: &Helper<'reg, 'rc>,
+        _: &'reg Handlebars<'reg>,
+        ctx: &'rc Context,
+        _: &mut RenderContext<'reg, 'rc>,
+        out: &mut dyn Output,
+    ) -> HelperResult {
+        let length = integer_param(h, 0, "random_string")?;
+        if length < 0 {
+            return Err(RenderError::new(
+                "Length is negative for helper \"random_string\"",
+            ));
+        }
+
+        let mut rng = self.0.rng(ctx);
+
+        let result = match h.hash_get("chars").map(|it| it.value()) {
+            Some(Json::String(chars)) if !chars.is_empty() => {
+                let chars = chars.chars().collect::<Vec<_>>();
+                (0..length)
+                    .map(|_| chars[rng.gen_range(0..chars.len())])
+                    .collect::<String>()
+            }
+            Some(_) => {
+                return Err(RenderError::new(
+                    "Param chars not a non-empty String for helper \"random_string\"",
+                ))
+            }
+            None => (0..length)
+                .map(|_| rng.sample(Alphanumeric) as char)
+                .collect::<String>(),
+        };
+
+        out.write(&result)?;
+
+        Ok(())
+    }
+}
+
+fn integer_param(h: &Helper, index: usize, helper_name: &str) -> Result<i64, RenderError> {
+    let param = h.param(index).ok_or_else(|| {
+        RenderError::new(format!("Param not found for helper \"{}\"", helper_name))
+    })?;
+
+    param.value().as_i64().ok_or_else(|| {
+        RenderError::new(format!(
+            "Param value not an integer for helper \"{}\"",
+            helper_name
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn create_hbs<'a>(random_source: Arc<RandomSource>) -> Handlebars<'a> {
+        let mut handlebars = Handlebars::new();
+        handlebars.register_helper("uuid", Box::new(UuidHelper(Arc::clone(&random_source))));
+        handlebars.register_helper("random", Box::new(RandomHelper(Arc::clone(&random_source))));
+        handlebars.register_helper("random_string", Box::new(RandomStringHelper(random_source)));
+
+        handlebars
+    }
+
+    #[test]
+    fn test_random_helpers() {
+        let handlebars = create_hbs(RandomSource::with_seed(None));
+
+        let uuid = handlebars.render_template("{{uuid}}", &json!({})).unwrap();
+        assert_eq!(36, uuid.len());
+        assert_eq!(Some('4'), uuid.chars().nth(14));
+
+        let port = handlebars
+            .render_template("{{random 49152 65535}}", &json!({}))
+            .unwrap()
+            .parse::<i64>()
+            .unwrap();
+        assert!((49152..=65535).contains(&port));
+
+        assert!(handlebars
+            .render_template("{{random 2 1}}", &json!({}))
+            .is_err());
+
+        let secret = handlebars
+            .render_template("{{random_string 24}}", &json!({}))
+            .unwrap();
+        assert_eq!(24, secret.len());
+        assert!(secret.chars().all(|it| it.is_ascii_alphanumeric()));
+
+        assert_eq!(
+            "aaaa",
+            handlebars
+                .render_template(r#"{{random_string 4 chars="a"}}"#, &json!({}))
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn test_seeded_random_helpers() {
+        let template = "{{uuid}} {{random 0 1000000}} {{random_string 16}}";
+        let file_context = |source_path: &str| {
+            json!({
+                "__template__": {
+                    "file": { "rootDir": "/template", "sourcePath": source_path }
+                }
+            })
+        };
+
+        let render = |seed: u64, source_path: &str| {
+            create_hbs(RandomSource::with_seed(Some(seed)))
+                .render_template(template, &file_context(source_path))
+                .unwrap()
+        };
+
+        assert_eq!(render(42, "/template/a.txt"), render(42, "/template/a.txt"));
+        assert_ne!(render(42, "/template/a.txt"), render(43, "/template/a.txt"));
+        assert_ne!(render(42, "/template/a.txt"), render(42, "/template/b.txt"));
+
+        // Repeated uses in the same file produce different values
+        let handlebars = create_hbs(RandomSource::with_seed(Some(42)));
+        let first = handlebars
+            .render_template("{{uuid}}", &file_context("/template/a.txt"))
+            .unwrap();
+        let second = handlebars
+            .render_template("{{uuid}}", &file_context("/template/a.txt"))
+            .unwrap();
+        assert_ne!(first, second);
+    }
+}
@@ -0,0 +1,139 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::fs::read_to_string;
+use std::io;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+use handlebars::Handlebars;
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::Engine;
+
+/// Creates the Rhai engine used for script helpers.
+///
+/// Rhai doesn't provide any filesystem or network access on its own, but `import` would
+/// load modules from the filesystem, so it's disabled. The limits make sure a faulty script
+/// cannot hang or exhaust the memory while rendering
+pub fn create_script_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_module_resolver(DummyModuleResolver::new());
+    engine.set_max_operations(1_000_000);
+    engine.set_max_call_levels(64);
+    engine.set_max_expr_depths(64, 32);
+    engine.set_max_string_size(1024 * 1024);
+    engine.set_max_array_size(10_000);
+    engine.set_max_map_size(10_000);
+
+    engine
+}
+
+pub fn register_script_helper(
+    handlebars: &mut Handlebars,
+    name: &str,
+    script_path: &Path,
+) -> io::Result<()> {
+    let script = read_to_string(script_path)?;
+
+    handlebars
+        .register_script_helper(name, &script)
+        .map_err(|err| Error::new(ErrorKind::InvalidData, format!("{}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    fn create_hbs<'a>() -> Handlebars<'a> {
+        let mut handlebars = Handlebars::new();
+        handlebars.set_engine(create_script_engine());
+
+        handlebars
+    }
+
+    #[test]
+    fn test_register_script_helper() -> io::Result<()> {
+        let temp_dir = tempdir()?;
+        let script_path = temp_dir.path().join("shout.rhai");
+        write(
+            &script_path,
+            r#"let text = params[0]; text.to_upper() + hash["suffix"]"#,
+        )?;
+
+        let mut handlebars = create_hbs();
+        register_script_helper(&mut handlebars, "shout", &script_path)?;
+
+        assert_eq!(
+            "HELLO!",
+            handlebars
+                .render_template(r#"{{shout name suffix="!"}}"#, &json!({ "name": "hello" }))
+                .unwrap()
+        );
+
+        let broken_script_path = temp_dir.path().join("broken.rhai");
+        write(&broken_script_path, "let = ;")?;
+
+        assert!(register_script_helper(&mut handlebars, "broken", &broken_script_path).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_script_sandbox() -> io::Result<()> {
+        let temp_dir = tempdir()?;
+
+        let import_script_path = temp_dir.path().join("import.rhai");
+        write(&import_script_path, r#"import "other" as other; other::x"#)?;
+        write(temp_dir.path().join("other.rhai"), "export const x = 1;")?;
+
+        let endless_script_path = temp_dir.path().join("endless.rhai");
+        write(&endless_script_path, "loop { }")?;
+
+        let mut handlebars = create_hbs();
+        register_script_helper(&mut handlebars, "import", &import_script_path)?;
+        register_script_helper(&mut handlebars, "endless", &endless_script_path)?;
+
+        assert!(handlebars
+            .render_template("{{import}}", &json!({}))
+            .is_err());
+        assert!(handlebars
+            .render_template("{{endless}}", &json!({}))
+            .is_err());
+
+        Ok(())
+    }
+}
@@ -30,6 +30,45 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
+use std::sync::Arc;
+
+use handlebars::Handlebars;
+
+pub use helper_command::CommandHelper;
+use helper_escape::EscapeHelper;
+pub use helper_escape::{escape_with_current_mode, with_escape_mode, EscapeMode};
+use helper_join_path::JOIN_PATH_HELPER;
+pub use helper_now::now;
+use helper_now::NOW_HELPER;
 pub use helper_package::PACKAGE_HELPER;
+use helper_random::{RandomHelper, RandomSource, RandomStringHelper, UuidHelper};
+pub use helper_script::{create_script_engine, register_script_helper};
 
+mod helper_command;
+mod helper_escape;
+mod helper_join_path;
+mod helper_now;
 mod helper_package;
+mod helper_random;
+mod helper_script;
+
+/// Registers the helpers Architect provides for file contents as well as names
+pub fn register_architect_helpers(handlebars: &mut Handlebars) {
+    let random_source = RandomSource::new();
+
+    handlebars.register_helper("uuid", Box::new(UuidHelper(Arc::clone(&random_source))));
+    handlebars.register_helper("random", Box::new(RandomHelper(Arc::clone(&random_source))));
+    handlebars.register_helper("random_string", Box::new(RandomStringHelper(random_source)));
+    handlebars.register_helper("now", Box::new(NOW_HELPER));
+    handlebars.register_helper("join_path", Box::new(JOIN_PATH_HELPER));
+
+    for (mode, name) in [
+        (EscapeMode::Json, "escape_json"),
+        (EscapeMode::Yaml, "escape_yaml"),
+        (EscapeMode::Toml, "escape_toml"),
+        (EscapeMode::Xml, "escape_xml"),
+        (EscapeMode::Shell, "escape_shell"),
+    ] {
+        handlebars.register_helper(name, Box::new(EscapeHelper(mode, name)));
+    }
+}
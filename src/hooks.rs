@@ -0,0 +1,458 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crossterm::style::Stylize;
+use handlebars::{Context, Handlebars};
+use json_patch::Patch;
+use serde_json::Value;
+use tempfile::tempdir;
+
+use crate::config::HookSpec;
+use crate::context::UnsafeContext;
+use crate::helpers::{with_escape_mode, EscapeMode};
+use crate::utils::ToolConfig;
+
+/// The environment variable that tells pre-generate hooks where the template is
+const TEMPLATE_DIR_VAR: &str = "ARCHITECT_TEMPLATE_DIR";
+
+/// Runs the hooks one after another in an empty temporary directory, stopping at the first failing
+/// fatal hook. Returns the context as patched by the hooks, or `None` if a fatal hook failed.
+///
+/// Files the hooks write must not end up in the rendered output, so they don't run in the template
+/// directory. It's passed in `ARCHITECT_TEMPLATE_DIR` instead, and programs specified as a path are
+/// still resolved relative to it.
+///
+/// Every hook receives the context as JSON on stdin and can write a JSON patch (RFC 6902) to
+/// stdout, which is applied to the context before the next hook runs
+pub fn run_pre_generate_hooks(
+    hooks: &[HookSpec],
+    template_dir: &Path,
+    hbs: &Handlebars,
+    context: Context,
+    tool_config: &ToolConfig,
+) -> Option<Context> {
+    let mut context = context;
+
+    let scratch_dir = match tempdir() {
+        Ok(scratch_dir) => scratch_dir,
+        Err(err) => {
+            eprintln!(
+                "{}",
+                format!("Failed to create a directory for the hooks ({})", err).red()
+            );
+            return None;
+        }
+    };
+
+    for hook in hooks {
+        let result = run_hook(
+            hook,
+            template_dir,
+            Some(scratch_dir.path()),
+            hbs,
+            &context,
+            tool_config,
+            Some(context.data()),
+        )
+        .and_then(|output| match output {
+            Some(output) => apply_patch(hook, context.data(), &output),
+            None => Ok(None),
+        });
+
+        match result {
+            Ok(Some(Value::Object(patched))) => context = UnsafeContext::new(patched).into(),
+            Ok(_) => (),
+            Err(err) if hook.fatal => {
+                eprintln!("{}", err.red());
+                return None;
+            }
+            Err(err) => eprintln!("{}", err.dark_yellow()),
+        }
+    }
+
+    Some(context)
+}
+
+/// Runs the hooks one after another in the target directory, stopping at the first failing fatal
+/// hook. Returns whether all fatal hooks succeeded
+pub fn run_post_generate_hooks(
+    hooks: &[HookSpec],
+    target_dir: &Path,
+    hbs: &Handlebars,
+    context: &Context,
+    tool_config: &ToolConfig,
+) -> bool {
+    for hook in hooks {
+        match run_hook(hook, target_dir, None, hbs, context, tool_config, None) {
+            Ok(_) => (),
+            Err(err) if hook.fatal => {
+                eprintln!("{}", err.red());
+                return false;
+            }
+            Err(err) => eprintln!("{}", err.dark_yellow()),
+        }
+    }
+
+    true
+}
+
+/// Runs the hook in `base_dir`, or its working directory inside of it. With a `scratch_dir` the hook
+/// runs there instead and `base_dir` is only used to resolve the program and passed as
+/// `ARCHITECT_TEMPLATE_DIR`. If there is an input, it's written to stdin and the output on stdout
+/// is returned, otherwise stdout is forwarded like stderr
+fn run_hook(
+    hook: &HookSpec,
+    base_dir: &Path,
+    scratch_dir: Option<&Path>,
+    hbs: &Handlebars,
+    context: &Context,
+    tool_config: &ToolConfig,
+    input: Option<&Value>,
+) -> Result<Option<Vec<u8>>, String> {
+    let name = hook.command.join(" ");
+
+    if let Some(when) = &hook.when {
+        let applies = when
+            .eval(context.data())
+            .map_err(|err| format!("Hook '{}' failed to evaluate its condition ({})", name, err))?;
+
+        if !applies {
+            if tool_config.verbose || tool_config.dry_run {
+                println!("{}", format!("Skipping hook: {}", name).dim());
+            }
+
+            return Ok(None);
+        }
+    }
+
+    // Arguments are passed as they are, so there's nothing to escape
+    let command = with_escape_mode(EscapeMode::None, || {
+        hook.command
+            .iter()
+            .map(|arg| hbs.render_template_with_context(arg, context))
+            .collect::<Result<Vec<_>, _>>()
+    })
+    .map_err(|err| format!("Hook '{}' failed to render its command ({})", name, err))?;
+
+    let working_dir = match hook.working_dir {
+        Some(working_dir) => base_dir.join(working_dir),
+        None => base_dir.to_path_buf(),
+    };
+
+    // Programs specified as a path are resolved relative to the working directory
+    let program = if Path::new(&command[0]).components().count() > 1 {
+        working_dir.join(&command[0])
+    } else {
+        PathBuf::from(&command[0])
+    };
+
+    println!("Running hook: {}", command.join(" ").bold());
+
+    if tool_config.dry_run {
+        return Ok(None);
+    }
+
+    let mut process = Command::new(&program);
+
+    match scratch_dir {
+        Some(scratch_dir) => process
+            .current_dir(scratch_dir)
+            .env(TEMPLATE_DIR_VAR, base_dir),
+        None => process.current_dir(&working_dir),
+    };
+
+    let mut child = process
+        .args(&command[1..])
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Hook '{}' failed to start ({})", name, err))?;
+
+    let prefix = format!(
+        "[{}]",
+        program.file_name().unwrap_or_default().to_string_lossy()
+    );
+
+    if let Some(input) = input {
+        let mut stdin = child.stdin.take().unwrap();
+        let input = serde_json::to_vec(input).unwrap();
+        thread::spawn(move || stdin.write_all(&input));
+    }
+
+    // stderr is always forwarded line by line while the hook is running
+    let stderr = child.stderr.take().unwrap();
+    let stderr_prefix = prefix.clone();
+    let stderr_reader = thread::spawn(move || forward_lines(stderr, &stderr_prefix, true));
+
+    let mut stdout = child.stdout.take().unwrap();
+    let capture_stdout = input.is_some();
+    let stdout_reader = thread::spawn(move || {
+        let mut buf = Vec::new();
+
+        if capture_stdout {
+            let _ = stdout.read_to_end(&mut buf);
+        } else {
+            forward_lines(stdout, &prefix, false);
+        }
+
+        buf
+    });
+
+    let status = child
+        .wait()
+        .map_err(|err| format!("Hook '{}' failed to run ({})", name, err))?;
+
+    let output = stdout_reader.join().unwrap_or_default();
+    let _ = stderr_reader.join();
+
+    if !status.success() {
+        return Err(format!("Hook '{}' failed with {}", name, status));
+    }
+
+    Ok(input.map(|_| output))
+}
+
+/// Applies the JSON patch a hook wrote to stdout, no output means no changes
+fn apply_patch(hook: &HookSpec, data: &Value, output: &[u8]) -> Result<Option<Value>, String> {
+    let name = hook.command.join(" ");
+
+    if output.iter().all(|it| it.is_ascii_whitespace()) {
+        return Ok(None);
+    }
+
+    let patch: Patch = serde_json::from_slice(output)
+        .map_err(|err| format!("Hook '{}' didn't write a valid JSON patch ({})", name, err))?;
+
+    let mut patched = data.clone();
+    json_patch::patch(&mut patched, &patch).map_err(|err| {
+        format!(
+            "Hook '{}' wrote a patch that can't be applied ({})",
+            name, err
+        )
+    })?;
+
+    if !patched.is_object() {
+        return Err(format!(
+            "Hook '{}' wrote a patch that replaces the context with a non-object",
+            name
+        ));
+    }
+
+    Ok(Some(patched))
+}
+
+fn forward_lines(reader: impl Read, prefix: &str, is_stderr: bool) {
+    for line in BufReader::new(reader).lines() {
+        let line = match line {
+            Ok(line) => line,
+            Err(_) => break,
+        };
+
+        if is_stderr {
+            eprintln!("{} {}", prefix.dim(), line);
+        } else {
+            println!("{} {}", prefix.dim(), line);
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use std::fs::{create_dir_all, read_dir, read_to_string, write};
+    use std::io;
+
+    use serde_json::{Map, Value};
+
+    use crate::condition::Expression;
+    use crate::context::UnsafeContext;
+    use crate::render::create_hbs;
+
+    use super::*;
+
+    const TOOL_CONFIG: ToolConfig<'_> = ToolConfig {
+        template: None,
+        no_history: false,
+        no_init: false,
+        ignore_checks: false,
+        dry_run: false,
+        verbose: false,
+        strict: false,
+        lint: false,
+        on_existing: None,
+        on_conflict: None,
+    };
+
+    fn hook<'a>(command: &[&'a str], when: Option<&str>, fatal: bool) -> HookSpec<'a> {
+        HookSpec {
+            command: command.iter().map(|it| it.to_string()).collect(),
+            working_dir: Some("sub"),
+            when: when.map(|it| Expression::parse(it).unwrap()),
+            fatal,
+        }
+    }
+
+    /// Pre-generate hooks can't have a working directory
+    fn pre_hook<'a>(command: &[&'a str], when: Option<&str>, fatal: bool) -> HookSpec<'a> {
+        HookSpec {
+            working_dir: None,
+            ..hook(command, when, fatal)
+        }
+    }
+
+    #[test]
+    fn test_run_post_generate_hooks() -> io::Result<()> {
+        let target_dir = tempdir()?;
+        create_dir_all(target_dir.path().join("sub"))?;
+
+        let mut context_map = Map::new();
+        context_map.insert("name".into(), Value::String("a & b".into()));
+        context_map.insert("npm".into(), Value::Bool(false));
+        let context = UnsafeContext::new(context_map).into();
+
+        let hbs = create_hbs(false);
+
+        let hooks = vec![
+            hook(&["sh", "-c", "echo '{{name}}' > out.txt"], None, true),
+            hook(&["sh", "-c", "echo skipped > out.txt"], Some("npm"), true),
+            hook(&["sh", "-c", "exit 1"], None, false),
+        ];
+
+        assert!(run_post_generate_hooks(
+            &hooks,
+            target_dir.path(),
+            &hbs,
+            &context,
+            &TOOL_CONFIG
+        ));
+        assert_eq!(
+            "a & b\n",
+            read_to_string(target_dir.path().join("sub/out.txt"))?
+        );
+
+        let hooks = vec![
+            hook(&["sh", "-c", "exit 1"], None, true),
+            hook(&["sh", "-c", "echo unreachable > out.txt"], None, true),
+        ];
+
+        assert!(!run_post_generate_hooks(
+            &hooks,
+            target_dir.path(),
+            &hbs,
+            &context,
+            &TOOL_CONFIG
+        ));
+        assert_eq!(
+            "a & b\n",
+            read_to_string(target_dir.path().join("sub/out.txt"))?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_run_pre_generate_hooks() -> io::Result<()> {
+        let template_dir = tempdir()?;
+        write(template_dir.path().join("check.sh"), "exit 0\n")?;
+
+        let mut context_map = Map::new();
+        context_map.insert("name".into(), Value::String("service".into()));
+        let context = UnsafeContext::new(context_map).into();
+
+        let hbs = create_hbs(false);
+
+        let hooks = vec![
+            pre_hook(
+                &[
+                    "sh",
+                    "-c",
+                    r#"cat > in.json; echo "[{\"op\": \"add\", \"path\": \"/input\", \"value\": $(cat in.json)}, {\"op\": \"add\", \"path\": \"/port\", \"value\": 8080}]""#,
+                ],
+                None,
+                true,
+            ),
+            pre_hook(
+                &["sh", "-c", r#"test -f "$ARCHITECT_TEMPLATE_DIR/check.sh""#],
+                None,
+                true,
+            ),
+            pre_hook(&["sh", "-c", "echo 'not a patch'"], None, false),
+            pre_hook(
+                &[
+                    "sh",
+                    "-c",
+                    r#"echo '[{"op": "replace", "path": "/name", "value": "{{name}}-{{port}}"}]'"#,
+                ],
+                Some("port == 8080"),
+                true,
+            ),
+        ];
+
+        let context =
+            run_pre_generate_hooks(&hooks, template_dir.path(), &hbs, context, &TOOL_CONFIG)
+                .unwrap();
+
+        assert_eq!(
+            &serde_json::json!({
+                "name": "service-8080",
+                "input": { "name": "service" },
+                "port": 8080
+            }),
+            context.data()
+        );
+
+        // Files written by the hooks must not be rendered along with the template
+        assert_eq!(1, read_dir(template_dir.path())?.count());
+
+        let hooks = vec![pre_hook(
+            &["sh", "-c", "echo 'Name is already taken' >&2; exit 1"],
+            None,
+            true,
+        )];
+
+        assert!(
+            run_pre_generate_hooks(&hooks, template_dir.path(), &hbs, context, &TOOL_CONFIG)
+                .is_none()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,590 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::Path;
+
+use handlebars::template::{HelperTemplate, Parameter, Template, TemplateElement};
+use handlebars::{Context, Handlebars, Helper, HelperResult, Output, RenderContext};
+use serde_json::{Map, Value};
+use walkdir::WalkDir;
+
+use crate::config::{Condition, Config};
+use crate::front_matter::strip_front_matter;
+use crate::helpers::PACKAGE_HELPER;
+use crate::render::{
+    collect_sources, create_hbs, register_helpers, translate_delimiters, TemplateError,
+};
+use crate::utils::text::read_text;
+use crate::utils::ToolConfig;
+
+/// Rendered by the helper probe for names that aren't helpers
+const MISSING_HELPER: &str = "\u{0}missing helper";
+
+/// Analyzes the context paths used by the files, names, partials, conditions and hooks of a
+/// template without rendering anything
+pub fn lint(root_dir: &Path, config: &Config, tool_config: &ToolConfig) -> io::Result<LintResult> {
+    // Helpers that can't be registered fail linting just like generating
+    register_helpers(&mut create_hbs(false), root_dir, config)?;
+
+    let errors = RefCell::new(vec![]);
+    let sources = collect_sources(root_dir, config, tool_config, &errors)?;
+
+    let mut analyzer = Analyzer::new(config);
+    analyzer.issues.extend(errors.into_inner());
+
+    let mut analyzed_names = HashSet::new();
+
+    for source in sources {
+        let rel_path = source.path.strip_prefix(root_dir).unwrap();
+
+        // Every templated name along the path, each directory only once
+        for ancestor in rel_path.ancestors().collect::<Vec<_>>().into_iter().rev() {
+            let name = match ancestor.file_name() {
+                Some(name) => name.to_string_lossy(),
+                None => continue,
+            };
+
+            if name.contains("{{") && analyzed_names.insert(ancestor.to_path_buf()) {
+                analyzer.analyze_source(ancestor, &name);
+            }
+        }
+
+        if source.is_template {
+            let (mut content, _) = read_text(&source.path, config.encoding(rel_path))?;
+
+            if let Some(front_matter) = &source.front_matter {
+                if let Some(target) = &front_matter.target {
+                    analyzer.analyze_source(rel_path, target);
+                }
+
+                if let Some(when) = &front_matter.when {
+                    when.paths()
+                        .iter()
+                        .for_each(|path| analyzer.use_path(rel_path, path, None));
+                }
+
+                // Blank lines keep the reported lines of the body in place
+                let lines = front_matter.file_options.lines;
+                content = "\n".repeat(lines) + strip_front_matter(&content, lines);
+            }
+
+            if let Some(delimiters) = config.delimiters(rel_path) {
+                content = translate_delimiters(&content, delimiters);
+            }
+
+            analyzer.analyze_source(rel_path, &content);
+        }
+    }
+
+    let partials_dir = root_dir.join(config.partials_dir);
+    if partials_dir.is_dir() {
+        for entry_result in WalkDir::new(&partials_dir).sort_by_file_name() {
+            let entry = entry_result?;
+            if entry.file_type().is_file() {
+                let rel_path = entry.path().strip_prefix(root_dir).unwrap();
+                let (content, _) = read_text(entry.path(), config.encoding(rel_path))?;
+                analyzer.analyze_source(rel_path, &content);
+            }
+        }
+    }
+
+    let config_path = Path::new(".architect.json");
+    for cond_spec in &config.filters.conditional_files {
+        match &cond_spec.condition {
+            Condition::Handlebars(condition) => {
+                analyzer.analyze_source(config_path, &format!("{{{{ {} }}}}", condition))
+            }
+            Condition::Expression(_, expression) => expression
+                .paths()
+                .iter()
+                .for_each(|path| analyzer.use_path(config_path, path, None)),
+        }
+    }
+
+    for hook in config
+        .hooks
+        .pre_generate
+        .iter()
+        .chain(config.hooks.post_generate.iter())
+    {
+        hook.command
+            .iter()
+            .for_each(|arg| analyzer.analyze_source(config_path, arg));
+
+        if let Some(when) = &hook.when {
+            when.paths()
+                .iter()
+                .for_each(|path| analyzer.use_path(config_path, path, None));
+        }
+    }
+
+    Ok(analyzer.finish())
+}
+
+/// Finds every undefined path and helper of a template in the context of a file, since Handlebars
+/// stops at the first one in strict mode. Templates that can't be parsed have no results
+pub(crate) fn find_undefined_references(
+    source: &Path,
+    template: &str,
+    config: &Config,
+    context: &Value,
+) -> Vec<TemplateError> {
+    let template = match Template::compile(template) {
+        Ok(template) => template,
+        Err(_) => return vec![],
+    };
+
+    let mut analyzer = Analyzer::new(config);
+    analyzer.context = Some(context);
+    analyzer.visit_template(source, &template, true, None);
+
+    analyzer.issues
+}
+
+#[derive(Debug)]
+pub struct LintResult {
+    /// Undefined context paths and templates that couldn't be parsed
+    pub issues: Vec<TemplateError>,
+    /// Questions that aren't referenced by any template, name or condition
+    pub unused_questions: Vec<String>,
+}
+
+impl LintResult {
+    pub fn is_empty(&self) -> bool {
+        self.issues.is_empty() && self.unused_questions.is_empty()
+    }
+}
+
+struct Analyzer<'a> {
+    questions: Vec<String>,
+    data_files: Vec<String>,
+    custom_helpers: HashSet<&'a str>,
+    /// Only used to find out whether a name refers to a helper
+    probe: Handlebars<'a>,
+    helper_names: HashMap<String, bool>,
+    /// The context of a file in strict mode, paths are looked up in it instead of being compared
+    /// to the questions and data files
+    context: Option<&'a Value>,
+    used_paths: HashSet<String>,
+    issues: Vec<TemplateError>,
+}
+
+impl<'a> Analyzer<'a> {
+    fn new(config: &'a Config) -> Self {
+        let mut probe = create_hbs(false);
+        probe.register_helper("package", Box::new(PACKAGE_HELPER));
+        probe.register_helper(
+            "helperMissing",
+            Box::new(
+                |_: &Helper,
+                 _: &Handlebars,
+                 _: &Context,
+                 _: &mut RenderContext,
+                 out: &mut dyn Output|
+                 -> HelperResult {
+                    out.write(MISSING_HELPER)?;
+                    Ok(())
+                },
+            ),
+        );
+
+        Analyzer {
+            questions: config
+                .questions
+                .iter()
+                .map(|it| it.path.names().join("."))
+                .collect(),
+            data_files: config
+                .data
+                .iter()
+                .map(|it| it.path.names().join("."))
+                .collect(),
+            custom_helpers: config.helpers.iter().map(|it| it.name).collect(),
+            probe,
+            helper_names: HashMap::new(),
+            context: None,
+            used_paths: HashSet::new(),
+            issues: vec![],
+        }
+    }
+
+    fn analyze_source(&mut self, source: &Path, content: &str) {
+        let first_issue = self.issues.len();
+
+        match Template::compile(content) {
+            Ok(template) => self.visit_template(source, &template, true, None),
+            Err(err) => self.issues.push(TemplateError {
+                line: err.line_no,
+                column: err.column_no,
+                ..TemplateError::new(source, err.reason.to_string())
+            }),
+        }
+
+        let new_issues = self.issues.split_off(first_issue);
+        self.issues
+            .extend(new_issues.into_iter().map(|it| it.with_snippet(content)));
+    }
+
+    /// Paths are only checked in the root scope, blocks like `each` change the scope to
+    /// values that can't be known statically
+    fn visit_template(
+        &mut self,
+        source: &Path,
+        template: &Template,
+        root_scope: bool,
+        location: Option<(usize, usize)>,
+    ) {
+        for (i, element) in template.elements.iter().enumerate() {
+            let location = template.mapping.get(i).map(|it| (it.0, it.1)).or(location);
+
+            match element {
+                TemplateElement::RawString(_) | TemplateElement::Comment(_) => (),
+                TemplateElement::Expression(helper)
+                | TemplateElement::HtmlExpression(helper)
+                | TemplateElement::HelperBlock(helper) => {
+                    self.visit_helper(source, helper, root_scope, location)
+                }
+                TemplateElement::DecoratorExpression(decorator)
+                | TemplateElement::DecoratorBlock(decorator)
+                | TemplateElement::PartialExpression(decorator)
+                | TemplateElement::PartialBlock(decorator) => {
+                    for param in decorator.params.iter().chain(decorator.hash.values()) {
+                        self.visit_param(source, param, root_scope, location);
+                    }
+
+                    if let Some(template) = &decorator.template {
+                        self.visit_template(source, template, root_scope, location);
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_helper(
+        &mut self,
+        source: &Path,
+        helper: &HelperTemplate,
+        root_scope: bool,
+        location: Option<(usize, usize)>,
+    ) {
+        let name = helper.name.as_name();
+
+        if helper.params.is_empty() && helper.hash.is_empty() {
+            // `{{name}}` is either a helper call or a value
+            if !name.is_some_and(|it| self.is_helper(it)) {
+                self.visit_param(source, &helper.name, root_scope, location);
+            }
+        } else if let Parameter::Subexpression(_) = helper.name {
+            self.visit_param(source, &helper.name, root_scope, location);
+        } else if let Some(name) = name.filter(|it| !self.is_helper(it)) {
+            let message = match self.context {
+                Some(_) => format!("Helper not defined: {:?}", name),
+                None => format!("'{}' isn't a helper", name),
+            };
+
+            self.push_issue(source, location, message);
+        }
+
+        // `if` and `unless` accept undefined values even in strict mode
+        let accepts_undefined =
+            self.context.is_some() && matches!(name, Some("if") | Some("unless"));
+
+        for param in helper.params.iter().chain(helper.hash.values()) {
+            if !(accepts_undefined && matches!(param, Parameter::Path(_))) {
+                self.visit_param(source, param, root_scope, location);
+            }
+        }
+
+        if let Some(template) = &helper.template {
+            let changes_scope = matches!(name, Some("each") | Some("with"));
+            self.visit_template(source, template, root_scope && !changes_scope, location);
+        }
+
+        if let Some(inverse) = &helper.inverse {
+            self.visit_template(source, inverse, root_scope, location);
+        }
+    }
+
+    fn visit_param(
+        &mut self,
+        source: &Path,
+        param: &Parameter,
+        root_scope: bool,
+        location: Option<(usize, usize)>,
+    ) {
+        match param {
+            Parameter::Path(handlebars::Path::Relative((_, raw))) => {
+                // `@root` always refers to the actual context
+                if let Some(path) = raw.strip_prefix("@root") {
+                    self.use_path(source, path.trim_start_matches(['.', '/']), location);
+                } else if root_scope {
+                    self.use_path(source, raw, location);
+                }
+            }
+            Parameter::Subexpression(subexpression) => {
+                if let TemplateElement::Expression(helper) = subexpression.element.as_ref() {
+                    self.visit_helper(source, helper, root_scope, location)
+                }
+            }
+            // Local variables like `@index`, literals and plain names aren't context paths
+            Parameter::Path(_) | Parameter::Name(_) | Parameter::Literal(_) => (),
+        }
+    }
+
+    fn use_path(&mut self, source: &Path, raw_path: &str, location: Option<(usize, usize)>) {
+        let raw_path = ["this.", "this/", "./"]
+            .iter()
+            .find_map(|it| raw_path.strip_prefix(it))
+            .unwrap_or(raw_path);
+
+        if raw_path.is_empty()
+            || raw_path == "this"
+            || raw_path.starts_with('@')
+            || raw_path.starts_with("..")
+        {
+            return;
+        }
+
+        let path = raw_path.replace('/', ".").replace(['[', ']'], "");
+
+        if !self.is_defined(&path) {
+            let message = match self.context {
+                Some(_) => format!("Variable {:?} not found in strict mode.", path),
+                None => format!("'{}' isn't defined by any question or data file", path),
+            };
+
+            self.push_issue(source, location, message);
+        }
+
+        self.used_paths.insert(path);
+    }
+
+    fn push_issue(&mut self, source: &Path, location: Option<(usize, usize)>, message: String) {
+        self.issues.push(TemplateError {
+            line: location.map(|it| it.0),
+            column: location.map(|it| it.1),
+            ..TemplateError::new(source, message)
+        });
+    }
+
+    fn is_defined(&self, path: &str) -> bool {
+        if let Some(context) = self.context {
+            return path
+                .split('.')
+                .try_fold(context, |value, name| match value {
+                    Value::Object(map) => map.get(name),
+                    Value::Array(items) => name.parse().ok().and_then(|it: usize| items.get(it)),
+                    _ => None,
+                })
+                .is_some();
+        }
+
+        path == "__template__"
+            || path.starts_with("__template__.")
+            || self
+                .questions
+                .iter()
+                .chain(self.data_files.iter())
+                .any(|it| is_related_path(path, it))
+    }
+
+    fn is_helper(&mut self, name: &str) -> bool {
+        if self.custom_helpers.contains(name) {
+            return true;
+        }
+
+        if let Some(&result) = self.helper_names.get(name) {
+            return result;
+        }
+
+        // There's no way to query the registered helpers, but calling an unknown name with a
+        // parameter falls back to helperMissing. Paths like `a.b` can't be called at all, and
+        // helpers may fail for the made up parameter
+        let probe_template = format!("{{{{{} 0}}}}", name);
+        let result = Template::compile(&probe_template).is_ok()
+            && match self
+                .probe
+                .render_template(&probe_template, &Value::Object(Map::new()))
+            {
+                Ok(rendered) => rendered != MISSING_HELPER,
+                Err(_) => true,
+            };
+
+        self.helper_names.insert(name.to_string(), result);
+        result
+    }
+
+    fn finish(mut self) -> LintResult {
+        let unused_questions = self
+            .questions
+            .iter()
+            .filter(|&question| {
+                !self
+                    .used_paths
+                    .iter()
+                    .any(|path| is_related_path(path, question))
+            })
+            .cloned()
+            .collect();
+
+        self.issues
+            .sort_by(|a, b| (&a.source, a.line, a.column).cmp(&(&b.source, b.line, b.column)));
+
+        LintResult {
+            issues: self.issues,
+            unused_questions,
+        }
+    }
+}
+
+/// Whether one path is the same as, or contained in, the other
+fn is_related_path(path: &str, other: &str) -> bool {
+    path == other
+        || path.starts_with(&format!("{}.", other))
+        || other.starts_with(&format!("{}.", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{create_dir_all, write};
+
+    use tempfile::tempdir;
+
+    use crate::config::read_config;
+
+    use super::*;
+
+    #[test]
+    fn test_lint() -> io::Result<()> {
+        let root_dir = tempdir()?;
+        let root_dir = root_dir.path();
+
+        create_dir_all(root_dir.join("src/{{ pkg }}"))?;
+        create_dir_all(root_dir.join(".architect/partials"))?;
+
+        write(
+            root_dir.join("README.md"),
+            "{{ author.name }} {{ uuid }}\n{{#each features}}{{ @key }} {{ other }}{{/each}}\n{{ autor.name }} {{ shout author.name }}\n",
+        )?;
+        write(root_dir.join("src/{{ pkg }}/Main.java"), "static")?;
+        write(
+            root_dir.join(".architect/partials/footer.hbs"),
+            "{{ @root.license }}",
+        )?;
+        write(root_dir.join("broken.txt"), "{{#if }}\n")?;
+        write(
+            root_dir.join("invalid.txt"),
+            "--- # architect\noverwrite: never\n---\n",
+        )?;
+        write(
+            root_dir.join("service.txt"),
+            "--- # architect\ntarget: '{{ servce }}.txt'\nwhen: useCi && enabled\n---\n{{ author.name }} {{ port }}\n",
+        )?;
+
+        let tool_config = ToolConfig {
+            template: None,
+            no_history: false,
+            no_init: false,
+            ignore_checks: false,
+            dry_run: false,
+            verbose: false,
+            strict: false,
+            lint: true,
+            on_existing: None,
+            on_conflict: None,
+        };
+
+        let config_json = r#"{
+            "questions": [
+                { "name": "author.name", "type": "Text" },
+                { "name": "pkg", "type": "Identifier" },
+                { "name": "features", "type": "Selection", "items": ["docker"], "multi": true },
+                { "name": "useCi", "type": "Option" },
+                { "name": "gradleVersion", "type": "Text" },
+                { "name": "useNpm", "type": "Option" },
+                { "name": "unused", "type": "Option" }
+            ],
+            "filters": {
+                "conditionalTemplates": [{ "when": "useCi && typo", "matcher": "ci/**" }]
+            },
+            "hooks": {
+                "preGenerate": [{ "command": ["./check.sh", "{{ gradleVersoin }}"], "when": "useNpm" }],
+                "postGenerate": [
+                    { "command": ["./gradlew", "wrapper", "{{ gradleVersion }}"] },
+                    { "command": ["npm", "install"], "when": "useNpn" }
+                ]
+            }
+        }"#;
+        let config = read_config(config_json, &tool_config)?;
+
+        let lint_result = lint(root_dir, &config, &tool_config)?;
+
+        assert_eq!(
+            vec![
+                ".architect/partials/footer.hbs:1:1: 'license' isn't defined by any question or data file",
+                ".architect.json: 'typo' isn't defined by any question or data file",
+                ".architect.json: 'useNpn' isn't defined by any question or data file",
+                ".architect.json:1:1: 'gradleVersoin' isn't defined by any question or data file",
+                "README.md:3:1: 'autor.name' isn't defined by any question or data file",
+                "README.md:3:18: 'shout' isn't a helper",
+                "broken.txt:2:1: invalid handlebars syntax.",
+                "invalid.txt: Invalid front matter (Unknown overwrite policy 'never' (expected skip, overwrite, prompt, backup))",
+                "service.txt: 'enabled' isn't defined by any question or data file",
+                "service.txt:1:1: 'servce' isn't defined by any question or data file",
+                "service.txt:5:19: 'port' isn't defined by any question or data file",
+            ],
+            lint_result
+                .issues
+                .iter()
+                .map(|it| it.to_string())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["unused"], lint_result.unused_questions);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_helper() {
+        let config = Config::empty();
+        let mut analyzer = Analyzer::new(&config);
+
+        assert!(analyzer.is_helper("uuid"));
+        assert!(analyzer.is_helper("package"));
+        assert!(analyzer.is_helper("if"));
+        assert!(!analyzer.is_helper("name"));
+        assert!(!analyzer.is_helper("author.name"));
+    }
+}
@@ -32,39 +32,55 @@
 
 use std::env;
 use std::ffi::OsString;
+use std::path::Path;
 use std::process::exit;
 
 use anyhow::bail;
 use crossterm::style::Stylize;
+use handlebars::Handlebars;
 use path_absolutize::Absolutize;
 use serde_json::Value;
 use tempfile::tempdir;
 
 use constants::{flags, options};
 
+use crate::answers::{answers_key, load_answers, store_answers};
 use crate::args::TrimmedValueOf;
 use crate::config::{load_config_file, read_config, Config};
-use crate::context::{build_context, UnsafeContext};
+use crate::context::{build_context, TemplateMetadata};
 use crate::dirs::{create_target_dir, find_template_dir, is_valid_target_dir};
-use crate::fetch::{copy_git_directory, init_git_repository, FetchOptions};
+use crate::fetch::{copy_git_directory, init_git_repository, read_git_info, FetchOptions};
+use crate::helpers::now;
+use crate::hooks::{run_post_generate_hooks, run_pre_generate_hooks};
+use crate::render::TemplateError;
 use crate::spec::{is_valid_template_spec, parse_template_spec};
+use crate::staging::StagingDir;
+use crate::trust::ensure_trusted;
 use crate::utils::context::pretty_print_context;
 use crate::utils::errors::ArchResult;
 use crate::utils::{constants, ToolConfig};
 
+mod answers;
 mod args;
+mod condition;
 mod config;
 mod context;
 mod dirs;
+mod existing;
 mod fetch;
+mod front_matter;
 mod helpers;
+mod hooks;
+mod lint;
 mod render;
 mod spec;
+mod staging;
 mod term;
+mod trust;
 mod utils;
 
 fn main() {
-    exit(match run(&mut env::args_os()) {
+    exit(match run(env::args_os()) {
         Ok(code) => code,
         Err(err) => {
             eprintln!("{}", format!("{:?}", err).red());
@@ -90,6 +106,10 @@ where
         println!("{}", "Verbose output enabled".dim());
     }
 
+    if tool_config.strict {
+        println!("{}", "Strict mode enabled".dim());
+    }
+
     let template_spec_raw = matches
         .value_of(constants::args::REPOSITORY)
         .unwrap()
@@ -105,14 +125,25 @@ where
         &env::current_dir()?,
         &template_spec,
         matches.value_of_trimmed(constants::args::TARGET),
-        tool_config.dry_run,
+        tool_config.dry_run || tool_config.lint,
     )?;
 
-    if !tool_config.dry_run && !is_valid_target_dir(&target_dir)? {
-        bail!("Invalid target directory: {}", target_dir.display());
-    }
+    if !tool_config.lint {
+        if !tool_config.dry_run
+            && !is_valid_target_dir(&target_dir, tool_config.on_existing.is_some())?
+        {
+            if target_dir.is_dir() && tool_config.on_existing.is_none() {
+                bail!(
+                    "Target directory is not empty: {} (use --on-existing to generate into it anyway)",
+                    target_dir.display()
+                );
+            }
+
+            bail!("Invalid target directory: {}", target_dir.display());
+        }
 
-    println!("Target directory: {}", target_dir.display(),);
+        println!("Target directory: {}", target_dir.display(),);
+    }
 
     let working_dir = tempdir()?;
     if tool_config.verbose {
@@ -123,9 +154,11 @@ where
         );
     }
 
+    let dirty = matches.is_present(flags::DIRTY);
+
     let fetch_options = FetchOptions {
         branch: matches.value_of(options::BRANCH),
-        dirty: matches.is_present(flags::DIRTY),
+        dirty,
         local_git: matches.is_present(flags::LOCAL_GIT),
         tool_config: &tool_config,
     };
@@ -141,10 +174,105 @@ where
         None
     };
 
-    let context = match &config {
-        Some(c) => build_context(c),
-        None => Ok(UnsafeContext::empty().into()),
-    }?;
+    let git_info = read_git_info(working_dir.path());
+
+    if let Some(config) = &config {
+        // Local changes aren't part of the commit, so they have to be confirmed every time
+        let commit = git_info
+            .as_ref()
+            .filter(|_| !dirty)
+            .map(|it| it.commit.as_str());
+
+        if !ensure_trusted(
+            config,
+            &template_spec.to_string(),
+            commit,
+            matches.is_present(flags::TRUST),
+        )? {
+            bail!("The template isn't trusted to run its commands");
+        }
+    }
+
+    if tool_config.lint {
+        return run_lint(
+            &template_path,
+            config.as_ref().unwrap_or(&Config::empty()),
+            &tool_config,
+        );
+    }
+
+    let answers_key = answers_key(&template_spec, tool_config.template);
+
+    let previous_answers = if matches.is_present(flags::FRESH) {
+        None
+    } else {
+        load_answers(&answers_key).unwrap_or_else(|err| {
+            eprintln!(
+                "{}",
+                format!(
+                    "{:#}",
+                    anyhow::Error::from(err).context("Failed to load previous answers")
+                )
+                .dark_yellow()
+            );
+
+            None
+        })
+    };
+
+    let metadata = TemplateMetadata {
+        source: template_spec.to_string(),
+        commit: git_info.as_ref().map(|it| it.commit.clone()),
+        // The requested branch is reset onto the default branch, so HEAD doesn't know it
+        branch: matches
+            .value_of(options::BRANCH)
+            .map(String::from)
+            .or_else(|| git_info.as_ref().and_then(|it| it.branch.clone())),
+        tag: git_info.and_then(|it| it.tag),
+        sub_template: tool_config.template,
+        architect_version: env!("CARGO_PKG_VERSION"),
+        generated_at: now().to_rfc3339(),
+        target_dir_name: target_dir
+            .file_name()
+            .map(|it| it.to_string_lossy().to_string())
+            .unwrap_or_default(),
+    };
+
+    let context = build_context(
+        config.as_ref().unwrap_or(&Config::empty()),
+        &metadata,
+        &template_path,
+        previous_answers.as_ref(),
+    )?;
+
+    if let Some(config) = config.as_ref().filter(|_| !tool_config.dry_run) {
+        if let Err(err) = store_answers(&answers_key, config, &context) {
+            eprintln!(
+                "{}",
+                format!(
+                    "{:#}",
+                    anyhow::Error::from(err).context("Failed to remember answers")
+                )
+                .dark_yellow()
+            );
+        }
+    }
+
+    let context = match config.as_ref() {
+        Some(config) if !config.hooks.pre_generate.is_empty() => {
+            match run_pre_generate_hooks(
+                &config.hooks.pre_generate,
+                &template_path,
+                &create_hooks_hbs(&template_path, config, &tool_config)?,
+                context,
+                &tool_config,
+            ) {
+                Some(context) => context,
+                None => bail!("The generation was aborted by a pre-generate hook"),
+            }
+        }
+        _ => context,
+    };
 
     if tool_config.verbose && *context.data() != Value::Null {
         println!("{}", "Using context".dim());
@@ -160,18 +288,52 @@ where
 
     println!();
 
+    // Nothing is written during a dry run, so there's nothing to roll back
+    let staging_dir = if tool_config.dry_run {
+        None
+    } else {
+        Some(StagingDir::create(&target_dir)?)
+    };
+
+    let output_dir = match &staging_dir {
+        Some(staging_dir) => {
+            if tool_config.verbose {
+                println!(
+                    "{}: {}",
+                    "Staging directory".dim(),
+                    staging_dir.path().display()
+                );
+            }
+
+            staging_dir.path().to_path_buf()
+        }
+        None => target_dir.clone(),
+    };
+
     let render_result = render::render(
         &template_path,
         &target_dir,
+        &output_dir,
         config.as_ref().unwrap_or(&Config::empty()),
         &context,
         &tool_config,
     )?;
 
-    if !tool_config.no_history && !is_subtemplate {
-        copy_git_directory(working_dir.path(), &target_dir, &tool_config)?;
-    } else if !tool_config.no_init {
-        init_git_repository(&target_dir, &tool_config)?;
+    let failed = !render_result.errors.is_empty() || !render_result.conflicts.is_empty();
+
+    // A failed generation is rolled back when the staging directory is dropped
+    if !failed {
+        if target_dir.join(".git").exists() {
+            // Generating into an existing repository, its history is left alone
+        } else if !tool_config.no_history && !is_subtemplate {
+            copy_git_directory(working_dir.path(), &output_dir, &tool_config)?;
+        } else if !tool_config.no_init {
+            init_git_repository(&output_dir, &tool_config)?;
+        }
+
+        if let Some(staging_dir) = staging_dir {
+            staging_dir.commit()?;
+        }
     }
 
     if tool_config.verbose {
@@ -208,18 +370,120 @@ where
         working_dir.into_path();
     }
 
-    println!(
-        "Finished scaffolding into directory {}",
-        target_dir.display()
-    );
+    if !render_result.errors.is_empty() {
+        eprintln!("There were rendering errors:");
+        print_template_errors(&render_result.errors);
+
+        eprintln!(
+            "Failed to render {} file(s), nothing was written to directory {}",
+            render_result.errors.len(),
+            target_dir.display()
+        );
+    } else if failed {
+        eprintln!(
+            "Multiple files would be written to the same target, nothing was written to directory {} (use --on-conflict to resolve conflicts)",
+            target_dir.display()
+        );
+    } else {
+        println!(
+            "Finished scaffolding into directory {}",
+            target_dir.display()
+        );
+    }
+
+    let hooks_succeeded = match config.as_ref() {
+        Some(config) if !failed && !config.hooks.post_generate.is_empty() => {
+            let succeeded = run_post_generate_hooks(
+                &config.hooks.post_generate,
+                &target_dir,
+                &create_hooks_hbs(&template_path, config, &tool_config)?,
+                &context,
+                &tool_config,
+            );
+
+            if !succeeded {
+                eprintln!(
+                    "A post-generate hook failed, the generated project is kept in directory {}",
+                    target_dir.display()
+                );
+            }
+
+            succeeded
+        }
+        _ => true,
+    };
 
     if tool_config.dry_run {
         println!("{}", "This was a dry run!".yellow());
     }
 
-    Ok(if render_result.conflicts.is_empty() {
+    Ok(if !render_result.errors.is_empty() {
+        3
+    } else if failed {
+        2
+    } else if !hooks_succeeded {
+        4
+    } else {
+        0
+    })
+}
+
+/// Hooks can use the same helpers as templates to render their commands
+fn create_hooks_hbs<'a>(
+    template_path: &Path,
+    config: &Config,
+    tool_config: &ToolConfig,
+) -> ArchResult<Handlebars<'a>> {
+    let mut handlebars = render::create_hbs(tool_config.strict || config.strict);
+    render::register_helpers(&mut handlebars, template_path, config)?;
+
+    Ok(handlebars)
+}
+
+fn print_template_errors(errors: &[TemplateError]) {
+    errors.iter().for_each(|error| {
+        eprintln!("  > {}", format!("{}", error).red());
+
+        if let (Some(line), Some(snippet)) = (error.line, &error.snippet) {
+            let gutter = " ".repeat(line.to_string().len());
+            eprintln!("      {} | {}", line, snippet);
+
+            if let Some(column) = error.column {
+                // Tabs are kept so the marker lines up with the snippet
+                let indent: String = snippet
+                    .chars()
+                    .take(column.saturating_sub(1))
+                    .map(|it| if it == '\t' { '\t' } else { ' ' })
+                    .collect();
+
+                eprintln!("      {} | {}{}", gutter, indent, "^".red());
+            }
+        }
+    });
+}
+
+fn run_lint(template_path: &Path, config: &Config, tool_config: &ToolConfig) -> ArchResult<i32> {
+    println!("Linting template");
+
+    let lint_result = lint::lint(template_path, config, tool_config)?;
+
+    print_template_errors(&lint_result.issues);
+
+    lint_result.unused_questions.iter().for_each(|question| {
+        eprintln!(
+            "  > {}",
+            format!("Question '{}' is never used", question).yellow()
+        )
+    });
+
+    Ok(if lint_result.is_empty() {
+        println!("No issues found");
         0
     } else {
-        2
+        eprintln!(
+            "Found {} issue(s)",
+            lint_result.issues.len() + lint_result.unused_questions.len()
+        );
+        1
     })
 }
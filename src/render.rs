@@ -30,10 +30,15 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
-use std::cmp::{max, min};
+use std::cell::RefCell;
+use std::cmp::max;
 use std::collections::HashMap;
 use std::env::var;
-use std::fs::{copy, create_dir_all, read_to_string, File};
+use std::error::Error as _;
+use std::fmt::{Display, Formatter};
+use std::fs::{
+    copy, create_dir_all, metadata, read, read_link, set_permissions, File, Permissions,
+};
 use std::io;
 use std::io::{Error, ErrorKind, Write};
 use std::path::{Path, PathBuf};
@@ -42,74 +47,109 @@ use std::sync::mpsc::channel;
 use std::sync::{Arc, Mutex};
 
 use crossterm::style::Stylize;
+use encoding_rs::{Encoding, UTF_8};
 use globset::GlobMatcher;
 use handlebars::{Context, Handlebars, RenderError};
-use indicatif::{MultiProgress, ProgressBar};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget};
 use lazy_static::lazy_static;
+use memchr::memmem;
 use path_absolutize::Absolutize;
 use serde_json::{Map, Value};
-use walkdir::WalkDir;
+use walkdir::{DirEntry, WalkDir};
 
-use crate::config::{ConditionalFilesSpec, Config};
+use crate::condition::Expression;
+use crate::config::{Condition, ConditionalFilesSpec, Config, Delimiters, HelperSource};
 use crate::context::UnsafeContext;
-use crate::helpers::PACKAGE_HELPER;
-use crate::utils::reader::BufReader;
-use crate::utils::{ToolConfig, NEW_LINE_REGEX};
+use crate::existing::ExistingFiles;
+use crate::front_matter::{parse_front_matter, strip_front_matter, FileOptions, FrontMatter};
+use crate::helpers::{
+    create_script_engine, escape_with_current_mode, register_architect_helpers,
+    register_script_helper, with_escape_mode, CommandHelper, EscapeMode, PACKAGE_HELPER,
+};
+use crate::lint::find_undefined_references;
+use crate::utils::text::{is_binary, read_head, read_text, TextEncoding};
+use crate::utils::{symlink, ConflictStrategy, OnExisting, ToolConfig, NEW_LINE_REGEX};
 
 lazy_static! {
     static ref RENDER_PARALLELISM: usize =
         if let Ok(Ok(value)) = var("RENDER_PARALLELISM").map(|raw| usize::from_str(&raw)) {
             max(1, value)
         } else {
-            max(1, min(4, num_cpus::get() / 2))
+            (num_cpus::get() / 2).clamp(1, 4)
         };
-
-    static ref TEMPLATE_INSPECT_MAX_LINES: usize =
-        if let Ok(Ok(value)) = var("TEMPLATE_INSPECT_MAX_LINES").map(|raw| usize::from_str(&raw)) {
-            max(1, value)
-        } else {
-            // this feels like a sensible default, like who puts a Handlebars expression at the end of a 1000 line template?
-            25
-        };
-
     static ref HANDLEBARS_XTS: Vec<&'static str> = vec![".hbs", ".handlebars"];
 }
 
+/// Renders the template for `target_dir`, actually writing the files to `output_dir`
 pub fn render(
     root_dir: &Path,
     target_dir: &Path,
+    output_dir: &Path,
     config: &Config,
     context: &Context,
     tool_config: &ToolConfig,
 ) -> io::Result<RenderResult> {
-    let mut handlebars = create_hbs();
+    let strict = tool_config.strict || config.strict;
+
+    let mut handlebars = create_hbs(strict);
     handlebars.register_helper("package", Box::new(PACKAGE_HELPER));
+    register_helpers(&mut handlebars, root_dir, config)?;
 
-    let render_specs = build_render_specs(
+    let errors = RefCell::new(Vec::<TemplateError>::new());
+
+    let (render_specs, conflicts) = build_render_specs(
         root_dir,
         target_dir,
         config,
         &handlebars,
         context,
         tool_config,
+        &errors,
     )?;
 
+    // In strict mode nothing is written if a name or condition couldn't be rendered, and never
+    // if there are unresolved conflicts
+    if (strict && !errors.borrow().is_empty()) || !conflicts.is_empty() {
+        return Ok(RenderResult {
+            rendered_files: vec![],
+            conflicts,
+            errors: errors.into_inner(),
+        });
+    }
+
     // Creating new Handlebars instance without helpers that shouldn't be used in templates
-    handlebars = create_hbs();
+    handlebars = create_hbs(strict);
+    register_helpers(&mut handlebars, root_dir, config)?;
+    register_partials(&mut handlebars, root_dir, config, tool_config)?;
 
-    let (rspec_sender, rspec_receiver) = channel::<RenderSpec>();
+    // All render specs of a target are sent together, so they can be concatenated
+    let (rspec_sender, rspec_receiver) = channel::<Vec<RenderSpec>>();
     let rspec_receiver = Arc::new(Mutex::new(rspec_receiver));
 
     let rendered_files = Arc::new(Mutex::new(Vec::<RenderSpec>::new()));
+    let errors = Arc::new(Mutex::new(errors.into_inner()));
 
-    let all_progress = MultiProgress::new();
+    let existing_files = ExistingFiles::new(tool_config.on_existing, target_dir);
+
+    // Progress bars would draw over the diffs shown when prompting
+    let prompts = tool_config.on_existing == Some(OnExisting::Prompt)
+        || render_specs.values().flatten().any(|render_spec| {
+            render_spec.front_matter.and_then(|it| it.overwrite) == Some(OnExisting::Prompt)
+        });
+    let all_progress = if prompts {
+        MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+    } else {
+        MultiProgress::new()
+    };
 
-    let conflicts: Vec<RenderConflict> = crossbeam::scope(|scope| {
+    crossbeam::scope(|scope| {
         let handlebars = &handlebars;
+        let existing_files = &existing_files;
 
         (0..*RENDER_PARALLELISM).for_each(|worker_num| {
             let rspec_receiver = Arc::clone(&rspec_receiver);
             let rendered_files = Arc::clone(&rendered_files);
+            let errors = Arc::clone(&errors);
 
             let progress = all_progress.add(ProgressBar::new_spinner());
 
@@ -117,51 +157,36 @@ pub fn render(
                 progress.set_message(format!("[{}] Waiting...", worker_num));
 
                 match rspec_receiver.lock().unwrap().recv() {
-                    Ok(render_spec) => {
-                        if render_spec.is_template {
-                            progress.set_message(format!(
-                                "[{}] Rendering > {}",
-                                worker_num,
-                                render_spec.target.display()
-                            ));
-
-                            let file_context = build_file_context(context, &render_spec, root_dir);
-
-                            match render_template_to_file(
-                                &render_spec.source,
-                                &render_spec.target,
-                                handlebars,
-                                &file_context,
-                            ) {
-                                Ok(_) => rendered_files.lock().unwrap().push(render_spec),
-                                Err(err) => {
-                                    eprintln!(
-                                        "Failed to render template '{}' to '{}' ({})",
-                                        render_spec.source.display(),
-                                        render_spec.target.display(),
-                                        err
-                                    )
-                                }
-                            }
-                        } else {
-                            progress.set_message(format!(
-                                "[{}] Copying   > {}",
-                                worker_num,
-                                render_spec.target.display()
-                            ));
-
-                            match copy(&render_spec.source, &render_spec.target) {
-                                Ok(_) => (),
-                                Err(err) => {
-                                    eprintln!(
-                                        "Failed to copy '{}' to '{}' ({})",
-                                        render_spec.source.display(),
-                                        render_spec.target.display(),
-                                        err
-                                    )
-                                }
-                            }
-                        };
+                    Ok(render_specs) => {
+                        let target = render_specs[0].target.clone();
+
+                        progress.set_message(format!(
+                            "[{}] {} > {}",
+                            worker_num,
+                            if render_specs.iter().any(|it| it.is_template) {
+                                "Rendering"
+                            } else {
+                                "Copying  "
+                            },
+                            target.display()
+                        ));
+
+                        match write_target(
+                            &render_specs,
+                            &output_path(&target, target_dir, output_dir),
+                            root_dir,
+                            handlebars,
+                            context,
+                            config,
+                            existing_files,
+                        ) {
+                            Ok(true) => rendered_files
+                                .lock()
+                                .unwrap()
+                                .extend(render_specs.into_iter().filter(|it| it.is_template)),
+                            Ok(false) => print_skipped(&progress, &target, tool_config),
+                            Err(target_errors) => errors.lock().unwrap().extend(target_errors),
+                        }
                     }
                     Err(_) => {
                         progress.finish_with_message(format!("[{}] No more work", worker_num));
@@ -171,53 +196,35 @@ pub fn render(
             });
         });
 
-        let result = render_specs
+        render_specs
             .into_iter()
-            .filter_map(|(intended_target, render_specs)| {
-                if !tool_config.dry_run {
-                    match create_dir_all(intended_target.parent().unwrap()) {
-                        Ok(_) => (),
-                        Err(err) => {
-                            eprintln!(
-                                "Failed to create parent director(ies) of '{}' ({})",
-                                intended_target.display(),
-                                err
-                            );
-
-                            return None;
-                        }
-                    }
+            .for_each(|(intended_target, render_specs)| {
+                if tool_config.dry_run {
+                    return;
                 }
 
-                if render_specs.len() > 1 {
-                    Some(RenderConflict {
-                        intended_target,
-                        sources: render_specs
-                            .into_iter()
-                            .map(|it| {
-                                if !tool_config.dry_run {
-                                    rspec_sender.send(it.clone()).unwrap();
-                                }
-
-                                it.source
-                            })
-                            .collect(),
-                    })
-                } else {
-                    render_specs.into_iter().for_each(|it| {
-                        if !tool_config.dry_run {
-                            rspec_sender.send(it).unwrap()
-                        }
-                    });
-
-                    None
+                match create_dir_all(
+                    output_path(&intended_target, target_dir, output_dir)
+                        .parent()
+                        .unwrap(),
+                ) {
+                    Ok(_) => rspec_sender.send(render_specs).unwrap(),
+                    Err(err) => {
+                        errors.lock().unwrap().extend(render_specs.iter().map(|it| {
+                            TemplateError::new(
+                                it.source.strip_prefix(root_dir).unwrap(),
+                                format!(
+                                    "Failed to create parent director(ies) of '{}' ({})",
+                                    intended_target.display(),
+                                    err
+                                ),
+                            )
+                        }));
+                    }
                 }
-            })
-            .collect();
+            });
 
         drop(rspec_sender);
-
-        result
     })
     .unwrap();
 
@@ -228,19 +235,129 @@ pub fn render(
         .into_inner()
         .unwrap();
 
+    let errors = Arc::try_unwrap(errors).unwrap().into_inner().unwrap();
+
     Ok(RenderResult {
         rendered_files,
         conflicts,
+        errors,
     })
 }
 
-fn create_hbs<'a>() -> Handlebars<'a> {
+pub(crate) fn create_hbs<'a>(strict: bool) -> Handlebars<'a> {
     let mut instance = Handlebars::new();
+    instance.set_strict_mode(strict);
+    instance.set_engine(create_script_engine());
+    instance.register_escape_fn(escape_with_current_mode);
     handlebars_misc_helpers::register(&mut instance);
+    register_architect_helpers(&mut instance);
 
     instance
 }
 
+pub(crate) fn register_helpers(
+    handlebars: &mut Handlebars,
+    root_dir: &Path,
+    config: &Config,
+) -> io::Result<()> {
+    for helper in &config.helpers {
+        match helper.source {
+            HelperSource::Script(script) => {
+                register_script_helper(handlebars, helper.name, &root_dir.join(script)).map_err(
+                    |err| {
+                        Error::new(
+                            err.kind(),
+                            format!(
+                                "Failed to register helper '{}' from {} ({})",
+                                helper.name, script, err
+                            ),
+                        )
+                    },
+                )?
+            }
+            HelperSource::Command {
+                ref command,
+                timeout,
+            } => {
+                let program = Path::new(command[0]);
+                // Programs specified as a path are relative to the template, others are
+                // looked up in PATH
+                let program = if program.components().count() > 1 {
+                    root_dir.join(program)
+                } else {
+                    program.to_path_buf()
+                };
+
+                handlebars.register_helper(
+                    helper.name,
+                    Box::new(CommandHelper::new(
+                        helper.name,
+                        program,
+                        command[1..].iter().map(|&it| it.into()).collect(),
+                        root_dir.to_path_buf(),
+                        timeout,
+                    )),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn register_partials(
+    handlebars: &mut Handlebars,
+    root_dir: &Path,
+    config: &Config,
+    tool_config: &ToolConfig,
+) -> io::Result<()> {
+    let partials_dir = root_dir.join(config.partials_dir);
+    if !partials_dir.is_dir() {
+        return Ok(());
+    }
+
+    for entry_result in WalkDir::new(&partials_dir).sort_by_file_name() {
+        let entry = entry_result?;
+        if !entry.file_type().is_file() {
+            continue;
+        }
+
+        // The name of a partial is its path relative to the partials directory, always using
+        // `/` as separator and without the Handlebars extension, e.g. `license/header`
+        let rel_path = entry.path().strip_prefix(&partials_dir).unwrap();
+        let name = strip_handlebars_xt(
+            rel_path
+                .iter()
+                .map(|it| it.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/"),
+        );
+
+        let (content, _) = read_text(
+            entry.path(),
+            config.encoding(entry.path().strip_prefix(root_dir).unwrap()),
+        )?;
+
+        handlebars.register_partial(&name, content).map_err(|err| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!(
+                    "Failed to register partial '{}' from {} ({})",
+                    name,
+                    rel_path.display(),
+                    err
+                ),
+            )
+        })?;
+
+        if tool_config.verbose {
+            println!("{}", format!("Registered partial: {}", name).dim());
+        }
+    }
+
+    Ok(())
+}
+
 fn build_file_context(base_ctx: &Context, render_spec: &RenderSpec, root_dir: &Path) -> Context {
     let mut context_map = match base_ctx.data() {
         Value::Object(map) => map.clone(),
@@ -300,31 +417,396 @@ fn build_file_context(base_ctx: &Context, render_spec: &RenderSpec, root_dir: &P
     UnsafeContext::new(context_map).into()
 }
 
-fn render_template_to_file(
-    source: &Path,
-    target: &Path,
+/// Renders or copies the sources of a target to the output path, concatenating them if there are
+/// multiple. Returns whether the file was written considering how existing files should be handled
+fn write_target(
+    render_specs: &[RenderSpec],
+    output: &Path,
+    root_dir: &Path,
     hbs: &Handlebars,
     ctx: &Context,
+    config: &Config,
+    existing_files: &ExistingFiles,
+) -> Result<bool, Vec<TemplateError>> {
+    let target = &render_specs[0].target;
+    let policy = render_specs[0].front_matter.and_then(|it| it.overwrite);
+    let copy_error = |render_spec: &RenderSpec, err: Error| {
+        TemplateError::new(
+            render_spec.source.strip_prefix(root_dir).unwrap(),
+            format!("Failed to copy to '{}' ({})", target.display(), err),
+        )
+    };
+
+    let written = match render_specs {
+        [RenderSpec {
+            link_target: Some(link_target),
+            ..
+        }] => create_link(link_target, target, output, existing_files, policy).map_err(|err| {
+            vec![TemplateError::new(
+                render_specs[0].source.strip_prefix(root_dir).unwrap(),
+                format!("Failed to link '{}' ({})", target.display(), err),
+            )]
+        })?,
+        [render_spec] if !render_spec.is_template => {
+            copy_file(&render_spec.source, target, output, existing_files, policy)
+                .map_err(|err| vec![copy_error(render_spec, err)])?
+        }
+        _ => {
+            let content = read_contents(render_specs, root_dir, hbs, ctx, config)?;
+
+            write_file(target, output, &content, existing_files, policy).map_err(|err| {
+                vec![TemplateError::new(
+                    render_specs[0].source.strip_prefix(root_dir).unwrap(),
+                    format!("Failed to write '{}' ({})", target.display(), err),
+                )]
+            })?
+        }
+    };
+
+    // Setting permissions would change the target of a link
+    if written && render_specs[0].link_target.is_none() {
+        set_file_mode(render_specs, output, root_dir, config).map_err(|err| {
+            vec![TemplateError::new(
+                render_specs[0].source.strip_prefix(root_dir).unwrap(),
+                format!("Failed to set the mode of '{}' ({})", target.display(), err),
+            )]
+        })?;
+    }
+
+    Ok(written)
+}
+
+/// Renders or reads the sources of a target and concatenates them
+fn read_contents(
+    render_specs: &[RenderSpec],
+    root_dir: &Path,
+    hbs: &Handlebars,
+    ctx: &Context,
+    config: &Config,
+) -> Result<Vec<u8>, Vec<TemplateError>> {
+    let target = &render_specs[0].target;
+    let read_error = |render_spec: &RenderSpec, err: Error| {
+        TemplateError::new(
+            render_spec.source.strip_prefix(root_dir).unwrap(),
+            format!("Failed to copy to '{}' ({})", target.display(), err),
+        )
+    };
+
+    let mut content = Vec::new();
+
+    for render_spec in render_specs {
+        if render_spec.is_template {
+            let file_context = build_file_context(ctx, render_spec, root_dir);
+
+            let source = render_spec.source.strip_prefix(root_dir).unwrap();
+            let encoding = config.encoding(source);
+
+            let delimiters = config.delimiters(source);
+            let front_matter_lines = render_spec.front_matter.map_or(0, |it| it.lines);
+
+            let rendered = render_template(
+                &render_spec.source,
+                hbs,
+                &file_context,
+                config.escape_mode(target),
+                encoding,
+                delimiters,
+                front_matter_lines,
+            )
+            .map_err(|err| {
+                let error = TemplateError::from_io_error(source, &err);
+                let errors = match read_template(
+                    &render_spec.source,
+                    encoding,
+                    delimiters,
+                    front_matter_lines,
+                ) {
+                    Ok((template, _)) => {
+                        find_strict_errors(error, "", &template, hbs, &file_context, config)
+                    }
+                    Err(_) => vec![error],
+                };
+
+                let content = read_text(&render_spec.source, encoding).ok();
+
+                errors
+                    .into_iter()
+                    .map(|mut error| {
+                        // The front matter isn't part of the rendered template
+                        error.line = error.line.map(|line| line + front_matter_lines);
+
+                        match &content {
+                            Some((content, _)) => error.with_snippet(content),
+                            None => error,
+                        }
+                    })
+                    .collect::<Vec<_>>()
+            })?;
+
+            content.extend(rendered);
+        } else {
+            content.extend(
+                read(&render_spec.source).map_err(|err| vec![read_error(render_spec, err)])?,
+            );
+        }
+    }
+
+    Ok(content)
+}
+
+/// Carries the permissions of the first source over to the output, unless a mode is declared in
+/// the front matter or configured for one of the sources
+fn set_file_mode(
+    render_specs: &[RenderSpec],
+    output: &Path,
+    root_dir: &Path,
+    config: &Config,
 ) -> io::Result<()> {
-    let template = read_to_string(source)?;
+    let configured_mode = render_specs.iter().find_map(|render_spec| {
+        render_spec.front_matter.and_then(|it| it.mode).or_else(|| {
+            render_spec
+                .source
+                .strip_prefix(root_dir)
+                .ok()
+                .and_then(|source| config.file_mode(source))
+        })
+    });
+
+    // Configured modes are Unix permissions, other platforms only keep the source permissions
+    let permissions = match configured_mode {
+        #[cfg(unix)]
+        Some(mode) => {
+            use std::os::unix::fs::PermissionsExt;
+
+            Permissions::from_mode(mode)
+        }
+        _ => metadata(&render_specs[0].source)?.permissions(),
+    };
+
+    set_permissions(output, permissions)
+}
+
+/// Renders the template without the lines of its front matter, the result is encoded like the
+/// template itself
+fn render_template(
+    source: &Path,
+    hbs: &Handlebars,
+    ctx: &Context,
+    escape_mode: EscapeMode,
+    declared_encoding: Option<&'static Encoding>,
+    delimiters: Option<&Delimiters>,
+    front_matter_lines: usize,
+) -> io::Result<Vec<u8>> {
+    let (template, encoding) =
+        read_template(source, declared_encoding, delimiters, front_matter_lines)?;
+
+    with_escape_mode(escape_mode, || {
+        hbs.render_template_with_context(&template, ctx)
+    })
+    .map(|rendered| encoding.encode(&rendered))
+    // The render error is kept as the source, so its location can be reported later
+    .map_err(|err| Error::new(ErrorKind::InvalidData, err))
+}
+
+/// Reads the template as it's rendered, i.e. without the lines of its front matter and with the
+/// default delimiters
+fn read_template(
+    source: &Path,
+    declared_encoding: Option<&'static Encoding>,
+    delimiters: Option<&Delimiters>,
+    front_matter_lines: usize,
+) -> io::Result<(String, TextEncoding)> {
+    let (mut template, encoding) = read_text(source, declared_encoding)?;
+
+    if front_matter_lines > 0 {
+        template = strip_front_matter(&template, front_matter_lines).to_string();
+    }
+
+    if let Some(delimiters) = delimiters {
+        template = translate_delimiters(&template, delimiters);
+    }
+
+    Ok((template, encoding))
+}
+
+/// Handlebars stops at the first undefined reference in strict mode, so the template is analyzed
+/// to report all of them. The error of Handlebars is kept if the analysis didn't find it, e.g.
+/// because it occurred inside of an `each` block
+fn find_strict_errors(
+    error: TemplateError,
+    part: &str,
+    template: &str,
+    hbs: &Handlebars,
+    ctx: &Context,
+    config: &Config,
+) -> Vec<TemplateError> {
+    if !hbs.strict_mode() {
+        return vec![error];
+    }
+
+    let mut errors = find_undefined_references(&error.source, template, config, ctx.data());
+
+    if !part.is_empty() {
+        errors
+            .iter_mut()
+            .for_each(|it| it.message = format!("in {}: {}", part, it.message));
+    }
+
+    if !errors
+        .iter()
+        .any(|it| (it.line, it.column) == (error.line, error.column))
+    {
+        errors.push(error);
+    }
+
+    errors.sort_by_key(|it| (it.line, it.column));
+    errors
+}
+
+fn write_file(
+    target: &Path,
+    output: &Path,
+    content: &[u8],
+    existing_files: &ExistingFiles,
+    policy: Option<OnExisting>,
+) -> io::Result<bool> {
+    if !existing_files.should_write(target, output, content, policy)? {
+        return Ok(false);
+    }
+
+    let mut output_file = File::create(output)?;
+    output_file.write_all(content)?;
+
+    Ok(true)
+}
+
+/// Copies the file to the output path, returns whether the file was written considering how
+/// existing files should be handled
+fn copy_file(
+    source: &Path,
+    target: &Path,
+    output: &Path,
+    existing_files: &ExistingFiles,
+    policy: Option<OnExisting>,
+) -> io::Result<bool> {
+    if target.exists() && !existing_files.should_write(target, output, &read(source)?, policy)? {
+        return Ok(false);
+    }
+
+    copy(source, output)?;
+
+    Ok(true)
+}
+
+/// Creates a symbolic link at the output path, returns whether it was created considering how
+/// existing files should be handled
+fn create_link(
+    link_target: &Path,
+    target: &Path,
+    output: &Path,
+    existing_files: &ExistingFiles,
+    policy: Option<OnExisting>,
+) -> io::Result<bool> {
+    if target.symlink_metadata().is_ok() {
+        if read_link(target).is_ok_and(|it| it == link_target) {
+            return Ok(false);
+        }
+
+        let content = link_target.to_string_lossy();
+
+        if !existing_files.should_write(target, output, content.as_bytes(), policy)? {
+            return Ok(false);
+        }
+    }
+
+    symlink(link_target, output)?;
+
+    Ok(true)
+}
+
+/// Lists every file of the template, regardless of conditions and names, e.g. to analyze them
+/// without a context
+pub(crate) fn collect_sources(
+    root_dir: &Path,
+    config: &Config,
+    tool_config: &ToolConfig,
+    errors: &RefCell<Vec<TemplateError>>,
+) -> io::Result<Vec<TemplateSource>> {
+    let mut sources = vec![];
+
+    let walk = WalkDir::new(root_dir).sort_by_file_name();
+
+    for entry_result in walk
+        .into_iter()
+        .filter_entry(|entry| {
+            is_template_entry(
+                entry.path(),
+                entry.metadata().is_ok_and(|meta| meta.is_dir()),
+                root_dir,
+                config,
+                tool_config,
+            )
+        })
+        .skip(1)
+    {
+        let entry = entry_result?;
+
+        if !entry.metadata()?.is_dir() {
+            let (front_matter, is_template) = inspect_source(&entry, root_dir, config, errors)?;
 
-    let rendered = match hbs.render_template_with_context(&template, ctx) {
-        Ok(result) => result,
-        Err(err) => return Err(Error::new(ErrorKind::InvalidData, format!("{}", err))),
+            sources.push(TemplateSource {
+                path: entry.into_path(),
+                is_template,
+                front_matter,
+            });
+        }
+    }
+
+    Ok(sources)
+}
+
+/// Reads the front matter of the file and finds out whether it's a template
+fn inspect_source(
+    entry: &DirEntry,
+    root_dir: &Path,
+    config: &Config,
+    errors: &RefCell<Vec<TemplateError>>,
+) -> io::Result<(Option<FrontMatter>, bool)> {
+    let path = entry.path();
+    let source = path.strip_prefix(root_dir).unwrap();
+
+    let is_potential_template = if let Some(templates) = &config.filters.templates {
+        matches_globs(templates, root_dir, path)
+    } else if let Some(non_templates) = &config.filters.non_templates {
+        !matches_globs(non_templates, root_dir, path)
+    } else {
+        true
     };
 
-    let mut target_file = File::create(target)?;
-    target_file.write_all(rendered.as_bytes())
+    if entry.path_is_symlink()
+        || matches_globs(&config.filters.raw, root_dir, path)
+        || !is_potential_template
+    {
+        return Ok((None, false));
+    }
+
+    let front_matter = read_front_matter(path, source, config.encoding(source), errors)?;
+
+    let is_template = front_matter.is_some()
+        || is_hbs_template(path, config.encoding(source), config.delimiters(source))?;
+
+    Ok((front_matter, is_template))
 }
 
-fn build_render_specs(
+pub(crate) fn build_render_specs(
     root_dir: &Path,
     target_dir: &Path,
     config: &Config,
     hbs: &Handlebars,
     ctx: &Context,
     tool_config: &ToolConfig,
-) -> io::Result<HashMap<PathBuf, Vec<RenderSpec>>> {
+    errors: &RefCell<Vec<TemplateError>>,
+) -> io::Result<(RenderSpecs, Vec<RenderConflict>)> {
     let mut render_specs: HashMap<PathBuf, Vec<RenderSpec>> = HashMap::new();
 
     // We already add the root directory to the stack here so we don't have to deal with it
@@ -334,19 +816,21 @@ fn build_render_specs(
         target_path: Some(target_dir.to_path_buf()),
     }];
 
-    let walk = WalkDir::new(root_dir);
+    // Sorted, so resolved conflicts and seeded helpers in names are reproducible
+    let walk = WalkDir::new(root_dir).sort_by_file_name();
 
     for entry_result in walk
         .into_iter()
         .filter_entry(|entry| {
             include_dir_entry(
                 entry.path(),
-                entry.metadata().map_or(false, |meta| meta.is_dir()),
+                entry.metadata().is_ok_and(|meta| meta.is_dir()),
                 root_dir,
                 config,
                 hbs,
                 ctx,
                 tool_config,
+                errors,
             )
         })
         // We skip the root directory here, otherwise we would have to handle it separately in the loop body
@@ -357,7 +841,7 @@ fn build_render_specs(
 
         while dir_context_stack
             .last()
-            .map_or(false, |it| !entry.path().starts_with(&it.source_path))
+            .is_some_and(|it| !entry.path().starts_with(&it.source_path))
         {
             dir_context_stack.pop();
         }
@@ -386,7 +870,7 @@ fn build_render_specs(
                 .to_string();
 
             let entry_target_dir_name = if it_contains_template(&entry_dir_name) {
-                create_entry_target_dir_name(&entry_dir_name, hbs, ctx)
+                create_entry_target_dir_name(entry.path(), root_dir, config, hbs, ctx, errors)
             } else {
                 Some(entry_dir_name)
             };
@@ -421,44 +905,78 @@ fn build_render_specs(
             let current_dir_ctx = dir_context_stack.last().unwrap();
 
             let source_file_name = entry.file_name().to_string_lossy().to_string();
+            let source_rel = entry.path().strip_prefix(root_dir).unwrap();
+            let is_link = entry.path_is_symlink();
+
+            let (front_matter, is_template) = inspect_source(&entry, root_dir, config, errors)?;
+
+            if let Some(when) = front_matter.as_ref().and_then(|it| it.when.as_ref()) {
+                if !is_included_by_front_matter(
+                    when,
+                    source_rel,
+                    ctx,
+                    tool_config.strict || config.strict,
+                    tool_config,
+                    errors,
+                ) {
+                    continue;
+                }
+            }
 
-            let is_potential_template = if let Some(templates) = &config.filters.templates {
-                matches_globs(templates, root_dir, entry.path())
-            } else if let Some(non_templates) = &config.filters.non_templates {
-                !matches_globs(non_templates, root_dir, entry.path())
+            let target = if let Some(raw_target) =
+                front_matter.as_ref().and_then(|it| it.target.as_deref())
+            {
+                match create_front_matter_target(
+                    raw_target, source_rel, target_dir, hbs, ctx, errors,
+                )? {
+                    Some(target) => target,
+                    None => continue,
+                }
             } else {
-                true
-            };
+                let mut target_file_name = if it_contains_template(&source_file_name) {
+                    create_entry_target_file_name(entry.path(), root_dir, config, hbs, ctx, errors)
+                } else {
+                    source_file_name
+                };
+
+                if is_template {
+                    target_file_name = strip_handlebars_xt(target_file_name);
+                }
 
-            let is_template = is_potential_template && is_hbs_template(entry.path())?;
+                create_proper_target_path(
+                    target_dir,
+                    current_dir_ctx.target_path.as_ref().unwrap(),
+                    &target_file_name,
+                )
+                .unwrap_or_else(|| {
+                    current_dir_ctx
+                        .target_path
+                        .as_ref()
+                        .unwrap()
+                        .join(entry.file_name())
+                })
+            };
 
-            let mut target_file_name = if it_contains_template(&source_file_name) {
-                create_entry_target_file_name(&source_file_name, hbs, ctx)
+            let link_target = if is_link {
+                match create_link_target(
+                    entry.path(),
+                    &target,
+                    root_dir,
+                    target_dir,
+                    hbs,
+                    ctx,
+                    errors,
+                )? {
+                    Some(link_target) => Some(link_target),
+                    None => continue,
+                }
             } else {
-                source_file_name
+                None
             };
 
-            if is_template {
-                target_file_name = strip_handlebars_xt(target_file_name);
-            }
-
-            let singular_target_path = create_proper_target_path(
-                target_dir,
-                current_dir_ctx.target_path.as_ref().unwrap(),
-                &target_file_name,
-            )
-            .unwrap_or_else(|| {
-                current_dir_ctx
-                    .target_path
-                    .as_ref()
-                    .unwrap()
-                    .join(entry.file_name())
-            });
-
-            let render_specs_vec = get_render_specs_vec(&mut render_specs, &singular_target_path);
+            let render_specs_vec = get_render_specs_vec(&mut render_specs, &target);
 
             let source = entry.into_path().absolutize()?.to_path_buf();
-            let target = get_numbered_path(singular_target_path, render_specs_vec.len());
 
             if tool_config.verbose || tool_config.dry_run {
                 let source_rel = source.strip_prefix(root_dir).unwrap();
@@ -476,6 +994,12 @@ fn build_render_specs(
                     }
 
                     println!();
+                } else if let Some(link_target) = &link_target {
+                    println!(
+                        "Linking file:   {} -> {}",
+                        format!("{}", source_rel.display()).yellow(),
+                        format!("{}", link_target.display()).yellow()
+                    );
                 } else {
                     println!(
                         "Copying file:   {}",
@@ -488,27 +1012,162 @@ fn build_render_specs(
                 source,
                 target,
                 is_template,
+                link_target,
+                front_matter: front_matter.map(|it| it.file_options),
             })
         };
     }
 
-    Ok(render_specs)
+    resolve_conflicts(render_specs, root_dir, target_dir, config, tool_config)
 }
 
-fn include_dir_entry(
-    path: &Path,
-    path_is_dir: bool,
+/// Reduces the render specs of every target with multiple sources according to the conflict
+/// strategy. Sources are in the order they were found in, i.e. sorted by path
+fn resolve_conflicts(
+    render_specs: RenderSpecs,
     root_dir: &Path,
+    target_dir: &Path,
     config: &Config,
-    hbs: &Handlebars,
-    ctx: &Context,
     tool_config: &ToolConfig,
-) -> bool {
-    is_not_git_dir_in_root(path, root_dir)
-        && is_not_sub_template_dir(path, path_is_dir, root_dir, tool_config)
-        && is_not_hidden_or_is_included(path, path_is_dir, root_dir, config, tool_config)
+) -> io::Result<(RenderSpecs, Vec<RenderConflict>)> {
+    let strategy = tool_config.on_conflict.unwrap_or(config.conflicts.strategy);
+
+    let mut resolved_render_specs = HashMap::new();
+    let mut conflicts = vec![];
+
+    for (intended_target, mut render_specs) in render_specs {
+        if render_specs.len() > 1 {
+            let resolved = match strategy {
+                ConflictStrategy::Error => None,
+                ConflictStrategy::FirstWins => Some(vec![render_specs[0].clone()]),
+                ConflictStrategy::LastWins => render_specs.last().map(|it| vec![it.clone()]),
+                ConflictStrategy::Concatenate => {
+                    if are_text_files(&render_specs)? {
+                        Some(render_specs.clone())
+                    } else {
+                        None
+                    }
+                }
+                ConflictStrategy::Priority => {
+                    select_by_priority(&render_specs, &config.conflicts.priority, root_dir)
+                        .map(|it| vec![it.clone()])
+                }
+            };
+
+            match resolved {
+                Some(resolved) => {
+                    if tool_config.verbose || tool_config.dry_run {
+                        println!(
+                            "Resolved conflict: {} <= {}",
+                            format!(
+                                "{}",
+                                intended_target.strip_prefix(target_dir).unwrap().display()
+                            )
+                            .yellow(),
+                            resolved
+                                .iter()
+                                .map(|it| format!(
+                                    "{}",
+                                    it.source.strip_prefix(root_dir).unwrap().display()
+                                ))
+                                .collect::<Vec<_>>()
+                                .join(" + ")
+                        );
+                    }
+
+                    render_specs = resolved;
+                }
+                None => {
+                    conflicts.push(RenderConflict {
+                        intended_target,
+                        sources: render_specs.into_iter().map(|it| it.source).collect(),
+                    });
+
+                    continue;
+                }
+            }
+        }
+
+        resolved_render_specs.insert(intended_target, render_specs);
+    }
+
+    conflicts.sort_by(|a, b| a.intended_target.cmp(&b.intended_target));
+
+    Ok((resolved_render_specs, conflicts))
+}
+
+/// Only text files can be concatenated, templates always are
+fn are_text_files(render_specs: &[RenderSpec]) -> io::Result<bool> {
+    for render_spec in render_specs {
+        if render_spec.link_target.is_some() {
+            return Ok(false);
+        }
+
+        if !render_spec.is_template && std::str::from_utf8(&read(&render_spec.source)?).is_err() {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
+
+/// The source matching the earliest priority glob, if it is the only one matching it
+fn select_by_priority<'spec>(
+    render_specs: &'spec [RenderSpec],
+    priority: &[GlobMatcher],
+    root_dir: &Path,
+) -> Option<&'spec RenderSpec> {
+    let ranked = render_specs
+        .iter()
+        .filter_map(|render_spec| {
+            priority
+                .iter()
+                .position(|glob| {
+                    matches_globs(std::slice::from_ref(glob), root_dir, &render_spec.source)
+                })
+                .map(|rank| (rank, render_spec))
+        })
+        .collect::<Vec<_>>();
+
+    let best_rank = ranked.iter().map(|(rank, _)| *rank).min()?;
+    let mut best = ranked.into_iter().filter(|(rank, _)| *rank == best_rank);
+
+    match (best.next(), best.next()) {
+        (Some((_, render_spec)), None) => Some(render_spec),
+        _ => None,
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn include_dir_entry(
+    path: &Path,
+    path_is_dir: bool,
+    root_dir: &Path,
+    config: &Config,
+    hbs: &Handlebars,
+    ctx: &Context,
+    tool_config: &ToolConfig,
+    errors: &RefCell<Vec<TemplateError>>,
+) -> bool {
+    is_template_entry(path, path_is_dir, root_dir, config, tool_config)
+        && is_conditionally_included(path, root_dir, config, hbs, ctx, tool_config, errors)
+}
+
+/// Whether the path is part of the template at all, regardless of any conditions
+fn is_template_entry(
+    path: &Path,
+    path_is_dir: bool,
+    root_dir: &Path,
+    config: &Config,
+    tool_config: &ToolConfig,
+) -> bool {
+    is_not_git_dir_in_root(path, root_dir)
+        && is_not_partials_dir(path, root_dir, config)
+        && is_not_helper_script(path, root_dir, config)
+        && is_not_data_file(path, root_dir, config)
+        && is_not_sub_template_dir(path, path_is_dir, root_dir, tool_config)
+        && is_not_hidden_or_is_included(path, path_is_dir, root_dir, config, tool_config)
         && is_not_excluded(path, root_dir, config, tool_config)
-        && is_conditionally_included(path, root_dir, config, hbs, ctx, tool_config)
 }
 
 #[inline]
@@ -516,6 +1175,26 @@ fn is_not_git_dir_in_root(path: &Path, root_dir: &Path) -> bool {
     path != root_dir.join(".git")
 }
 
+#[inline]
+fn is_not_partials_dir(path: &Path, root_dir: &Path, config: &Config) -> bool {
+    path != root_dir.join(config.partials_dir)
+}
+
+#[inline]
+fn is_not_helper_script(path: &Path, root_dir: &Path, config: &Config) -> bool {
+    !config.helpers.iter().any(|helper| {
+        matches!(helper.source, HelperSource::Script(script) if path == root_dir.join(script))
+    })
+}
+
+#[inline]
+fn is_not_data_file(path: &Path, root_dir: &Path, config: &Config) -> bool {
+    !config
+        .data
+        .iter()
+        .any(|data_file| path == root_dir.join(data_file.file))
+}
+
 #[inline]
 fn is_not_sub_template_dir(
     path: &Path,
@@ -593,7 +1272,10 @@ fn is_conditionally_included(
     hbs: &Handlebars,
     ctx: &Context,
     tool_config: &ToolConfig,
+    errors: &RefCell<Vec<TemplateError>>,
 ) -> bool {
+    let strict = tool_config.strict || config.strict;
+
     path.strip_prefix(root_dir)
         .map(|globbing_path| {
             let mut matched_cond_spec = false;
@@ -608,10 +1290,10 @@ fn is_conditionally_included(
 
                     match eval_condition(cond_spec, hbs, ctx) {
                         Ok(truthy) => truthy,
-                        Err(e) => {
+                        Err(e) if tool_config.ignore_checks || !strict => {
                             eprintln!(
                                 "{:?}",
-                                anyhow::Error::from(e).context(format!(
+                                e.context(format!(
                                     "Failed to evaluate condition '{}'",
                                     cond_spec.condition
                                 ))
@@ -619,6 +1301,23 @@ fn is_conditionally_included(
 
                             tool_config.ignore_checks
                         }
+                        // Only fails the generation in strict mode, like undefined references
+                        Err(e) => {
+                            let part = format!("condition '{}'", cond_spec.condition);
+                            let error = match e.downcast_ref::<RenderError>() {
+                                Some(err) => {
+                                    TemplateError::from_render_error(globbing_path, &part, err)
+                                        .with_snippet(&format!("{{{{ {} }}}}", cond_spec.condition))
+                                }
+                                None => {
+                                    TemplateError::new(globbing_path, format!("in {}: {}", part, e))
+                                }
+                            };
+
+                            errors.borrow_mut().push(error);
+
+                            false
+                        }
                     }
                 })
                 || !matched_cond_spec;
@@ -689,13 +1388,14 @@ fn eval_condition(
     conditional_files_spec: &ConditionalFilesSpec,
     handlebars: &Handlebars,
     context: &Context,
-) -> Result<bool, RenderError> {
-    match handlebars.render_template_with_context(
-        &format!("{{{{ {} }}}}", conditional_files_spec.condition),
-        context,
-    ) {
-        Ok(rendered) => Ok(is_truthy(&rendered)),
-        Err(e) => Err(e),
+) -> anyhow::Result<bool> {
+    match &conditional_files_spec.condition {
+        Condition::Handlebars(condition) => {
+            let rendered = handlebars
+                .render_template_with_context(&format!("{{{{ {} }}}}", condition), context)?;
+            Ok(is_truthy(&rendered))
+        }
+        Condition::Expression(_, expression) => Ok(expression.eval(context.data())?),
     }
 }
 
@@ -750,41 +1450,291 @@ fn get_render_specs_vec<'spec>(
 }
 
 fn create_entry_target_dir_name(
-    source_name: &str,
+    path: &Path,
+    root_dir: &Path,
+    config: &Config,
     handlebars: &Handlebars,
     context: &Context,
+    errors: &RefCell<Vec<TemplateError>>,
 ) -> Option<String> {
-    let (result, was_rendered) = render_line_template(source_name, handlebars, context);
-
-    if was_rendered {
-        Some(result)
-    } else {
-        None
-    }
+    render_entry_name(path, root_dir, config, handlebars, context, errors).ok()
 }
 
 fn create_entry_target_file_name(
-    source_name: &str,
+    path: &Path,
+    root_dir: &Path,
+    config: &Config,
     handlebars: &Handlebars,
     context: &Context,
+    errors: &RefCell<Vec<TemplateError>>,
 ) -> String {
-    render_line_template(source_name, handlebars, context).0
+    render_entry_name(path, root_dir, config, handlebars, context, errors)
+        .unwrap_or_else(|name| name)
 }
 
-fn is_hbs_template(path: &Path) -> io::Result<bool> {
-    Ok(BufReader::open(path)?
-        .into_iter()
-        .take(*TEMPLATE_INSPECT_MAX_LINES)
-        .any(|line| match line {
-            Ok(line) => it_contains_template(line.trim()),
+/// Reads the front matter of a potential template, invalid ones are reported as errors
+fn read_front_matter(
+    path: &Path,
+    source: &Path,
+    declared_encoding: Option<&'static Encoding>,
+    errors: &RefCell<Vec<TemplateError>>,
+) -> io::Result<Option<FrontMatter>> {
+    // Checking the start of the file is cheaper than reading every potential template
+    let head = read_head(path)?;
+    let head = head.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(&head);
+    if !head.starts_with(b"---") || is_binary(head) {
+        return Ok(None);
+    }
+
+    let content = match read_text(path, declared_encoding) {
+        Ok((content, _)) => content,
+        Err(err) if err.kind() == ErrorKind::InvalidData => return Ok(None),
+        Err(err) => return Err(err),
+    };
+
+    match parse_front_matter(&content) {
+        Ok(front_matter) => Ok(front_matter),
+        Err(err) => {
+            errors.borrow_mut().push(TemplateError::new(
+                source,
+                format!("Invalid front matter ({})", err),
+            ));
+
+            Ok(None)
+        }
+    }
+}
+
+/// Evaluates the `when` condition of a front matter, failures are only errors in strict mode
+fn is_included_by_front_matter(
+    when: &Expression,
+    source: &Path,
+    ctx: &Context,
+    strict: bool,
+    tool_config: &ToolConfig,
+    errors: &RefCell<Vec<TemplateError>>,
+) -> bool {
+    match when.eval(ctx.data()) {
+        Ok(included) => {
+            if !included && (tool_config.verbose || tool_config.dry_run) {
+                println!("{}", format!("Skipping path: {}", source.display()).dim());
+            }
+
+            included
+        }
+        Err(e) if tool_config.ignore_checks || !strict => {
+            eprintln!(
+                "{:?}",
+                anyhow::Error::from(e).context(format!(
+                    "Failed to evaluate the front matter condition of {}",
+                    source.display()
+                ))
+            );
+
+            tool_config.ignore_checks
+        }
+        Err(e) => {
+            errors.borrow_mut().push(TemplateError::new(
+                source,
+                format!("in front matter condition: {}", e),
+            ));
+
+            false
+        }
+    }
+}
+
+/// Renders the target path declared in a front matter, returns `None` if that fails or the path
+/// would be outside of the target directory
+fn create_front_matter_target(
+    raw_target: &str,
+    source: &Path,
+    target_dir: &Path,
+    handlebars: &Handlebars,
+    context: &Context,
+    errors: &RefCell<Vec<TemplateError>>,
+) -> io::Result<Option<PathBuf>> {
+    let rendered = match render_line_template(raw_target, handlebars, context) {
+        Ok(rendered) => PathBuf::from(rendered.trim()),
+        Err(err) => {
+            errors.borrow_mut().push(
+                TemplateError::from_render_error(source, "front matter target", &err)
+                    .with_snippet(raw_target),
+            );
+
+            return Ok(None);
+        }
+    };
+
+    let target = target_dir.join(&rendered);
+
+    if rendered.has_root()
+        || rendered.file_name().is_none()
+        || !target.absolutize()?.starts_with(target_dir.absolutize()?)
+    {
+        errors.borrow_mut().push(TemplateError::new(
+            source,
+            format!(
+                "The front matter target '{}' is outside of the target directory",
+                rendered.display()
+            ),
+        ));
+
+        return Ok(None);
+    }
+
+    Ok(Some(target))
+}
+
+/// Renders the target of a symbolic link, returns `None` if that fails or the link would point
+/// outside of the target directory
+fn create_link_target(
+    path: &Path,
+    target: &Path,
+    root_dir: &Path,
+    target_dir: &Path,
+    handlebars: &Handlebars,
+    context: &Context,
+    errors: &RefCell<Vec<TemplateError>>,
+) -> io::Result<Option<PathBuf>> {
+    let source = path.strip_prefix(root_dir).unwrap();
+    let raw_link_target = read_link(path)?.to_string_lossy().to_string();
+
+    let link_target = if it_contains_template(&raw_link_target) {
+        match render_line_template(&raw_link_target, handlebars, context) {
+            Ok(link_target) => PathBuf::from(link_target),
             Err(err) => {
-                eprintln!(
-                    "{}",
-                    format!("Failed to read line into the buffer ({})", err).red()
+                errors.borrow_mut().push(
+                    TemplateError::from_render_error(source, "link target", &err)
+                        .with_snippet(&raw_link_target),
                 );
-                false
+
+                return Ok(None);
+            }
+        }
+    } else {
+        PathBuf::from(raw_link_target)
+    };
+
+    let resolved = target.parent().unwrap().join(&link_target);
+
+    if link_target.has_root() || !resolved.absolutize()?.starts_with(target_dir.absolutize()?) {
+        errors.borrow_mut().push(TemplateError::new(
+            source,
+            format!(
+                "The link target '{}' is outside of the target directory",
+                link_target.display()
+            ),
+        ));
+
+        return Ok(None);
+    }
+
+    Ok(Some(link_target))
+}
+
+/// Renders the name of the entry, returning the unchanged name if that fails
+fn render_entry_name(
+    path: &Path,
+    root_dir: &Path,
+    config: &Config,
+    handlebars: &Handlebars,
+    context: &Context,
+    errors: &RefCell<Vec<TemplateError>>,
+) -> Result<String, String> {
+    let source_name = path.file_name().unwrap().to_string_lossy().to_string();
+
+    render_line_template(&source_name, handlebars, context).map_err(|err| {
+        let error =
+            TemplateError::from_render_error(path.strip_prefix(root_dir).unwrap(), "name", &err);
+
+        errors.borrow_mut().extend(
+            find_strict_errors(error, "name", &source_name, handlebars, context, config)
+                .into_iter()
+                .map(|it| it.with_snippet(&source_name)),
+        );
+
+        source_name
+    })
+}
+
+/// Whether the file is a template, i.e. it has a Handlebars extension or contains Handlebars
+/// expressions. Binary files and text that isn't valid in its encoding are copied verbatim
+fn is_hbs_template(
+    path: &Path,
+    declared_encoding: Option<&'static Encoding>,
+    delimiters: Option<&Delimiters>,
+) -> io::Result<bool> {
+    if has_handlebars_xt(path) {
+        return Ok(true);
+    }
+
+    if is_binary(&read_head(path)?) {
+        return Ok(false);
+    }
+
+    let bytes = read(path)?;
+    let encoding = TextEncoding::detect(&bytes, declared_encoding);
+
+    let (start, end) = delimiters.map_or(("{{", "}}"), |it| (it.start, it.end));
+
+    // UTF-8 doesn't have to be decoded, it only has to be valid
+    if encoding.encoding == UTF_8 {
+        return Ok(std::str::from_utf8(&bytes).is_ok() && contains_expression(&bytes, start, end));
+    }
+
+    Ok(encoding
+        .decode(&bytes)
+        .is_ok_and(|content| contains_expression(content.as_bytes(), start, end)))
+}
+
+/// Whether the start delimiter is followed by the end delimiter anywhere in the content,
+/// expressions can span multiple lines
+fn contains_expression(content: &[u8], start: &str, end: &str) -> bool {
+    memmem::find(content, start.as_bytes()).is_some_and(|start_i| {
+        memmem::find(&content[start_i + start.len()..], end.as_bytes()).is_some()
+    })
+}
+
+/// Rewrites expressions using alternate delimiters into Handlebars expressions. Literal `{{` are
+/// escaped, so they're rendered as they are
+pub(crate) fn translate_delimiters(template: &str, delimiters: &Delimiters) -> String {
+    let mut translated = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(ch) = rest.chars().next() {
+        if let Some(expression) = rest.strip_prefix(delimiters.start) {
+            if let Some(end_i) = expression.find(delimiters.end) {
+                translated.push_str("{{");
+                translated.push_str(&expression[..end_i]);
+                translated.push_str("}}");
+                rest = &expression[end_i + delimiters.end.len()..];
+                continue;
             }
-        }))
+        }
+
+        if let Some(after) = rest.strip_prefix("{{") {
+            translated.push_str("\\{{");
+            rest = after;
+        } else {
+            translated.push(ch);
+            rest = &rest[ch.len_utf8()..];
+        }
+    }
+
+    translated
+}
+
+fn has_handlebars_xt(path: &Path) -> bool {
+    let name_lower = path
+        .file_name()
+        .map(|it| it.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    // A file named only `.hbs` is a hidden file, not a template without a name
+    HANDLEBARS_XTS
+        .iter()
+        .any(|&xt| name_lower.len() > xt.len() && name_lower.ends_with(xt))
 }
 
 fn strip_handlebars_xt(name: String) -> String {
@@ -797,21 +1747,14 @@ fn strip_handlebars_xt(name: String) -> String {
         .unwrap_or(name)
 }
 
-fn render_line_template(template: &str, handlebars: &Handlebars, ctx: &Context) -> (String, bool) {
-    match handlebars.render_template_with_context(template, ctx) {
-        Ok(result) => (NEW_LINE_REGEX.replace_all(&result, " ").to_string(), true),
-        Err(error) => {
-            eprintln!(
-                "{}",
-                format!(
-                    "Failed to render small template: '{}' ({})",
-                    template, error
-                )
-                .red()
-            );
-            (template.to_string(), false)
-        }
-    }
+fn render_line_template(
+    template: &str,
+    handlebars: &Handlebars,
+    ctx: &Context,
+) -> Result<String, RenderError> {
+    handlebars
+        .render_template_with_context(template, ctx)
+        .map(|result| NEW_LINE_REGEX.replace_all(&result, " ").to_string())
 }
 
 fn create_proper_target_path(
@@ -852,34 +1795,35 @@ fn create_proper_target_path(
     }
 }
 
-fn get_numbered_path(base_path: PathBuf, number: usize) -> PathBuf {
-    if number == 0 {
-        base_path
-    } else {
-        let file_name = base_path.file_name().unwrap().to_string_lossy().to_string();
-        let numbered_name = if let Some(dot_i) = file_name.rfind('.') {
-            format!(
-                "{} ({}).{}",
-                file_name[..dot_i].to_string(),
-                number,
-                file_name[dot_i + 1..].to_string()
-            )
-        } else {
-            format!("{} ({})", file_name, number)
-        };
-
-        base_path.parent().map_or_else(
-            || PathBuf::from(&numbered_name),
-            |parent| parent.join(&numbered_name),
-        )
+fn print_skipped(progress: &ProgressBar, target: &Path, tool_config: &ToolConfig) {
+    if tool_config.verbose {
+        progress.println(format!("Skipping existing {}", target.display()));
     }
 }
 
+fn output_path(target: &Path, target_dir: &Path, output_dir: &Path) -> PathBuf {
+    output_dir.join(target.strip_prefix(target_dir).unwrap())
+}
+
+/// A file of the template as found by [`collect_sources`]
+pub(crate) struct TemplateSource {
+    pub path: PathBuf,
+    pub is_template: bool,
+    pub front_matter: Option<FrontMatter>,
+}
+
+/// The render specs of every target, multiple ones are conflicting or concatenated
+pub(crate) type RenderSpecs = HashMap<PathBuf, Vec<RenderSpec>>;
+
 #[derive(Clone, Debug)]
 pub struct RenderSpec {
     pub source: PathBuf,
     pub target: PathBuf,
-    is_template: bool,
+    pub is_template: bool,
+    /// The rendered target if the source is a symbolic link, relative to the directory of the link
+    pub link_target: Option<PathBuf>,
+    /// The options declared in the front matter of the template
+    pub front_matter: Option<FileOptions>,
 }
 
 #[derive(Clone, Debug)]
@@ -892,6 +1836,96 @@ struct DirContext {
 pub struct RenderResult {
     pub rendered_files: Vec<RenderSpec>,
     pub conflicts: Vec<RenderConflict>,
+    pub errors: Vec<TemplateError>,
+}
+
+/// A template, name or condition that couldn't be rendered, e.g. because of an undefined
+/// reference in strict mode
+#[derive(Debug)]
+pub struct TemplateError {
+    /// The path of the source file, relative to the template root
+    pub source: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    pub message: String,
+    /// The line of the source the error occurred in
+    pub snippet: Option<String>,
+}
+
+impl TemplateError {
+    pub(crate) fn new(source: &Path, message: String) -> Self {
+        TemplateError {
+            source: source.to_path_buf(),
+            line: None,
+            column: None,
+            message,
+            snippet: None,
+        }
+    }
+
+    fn from_render_error(source: &Path, part: &str, err: &RenderError) -> Self {
+        // Syntax errors are wrapped, but they know where they occurred
+        let (line, column, desc) = match err
+            .source()
+            .and_then(|it| it.downcast_ref::<handlebars::TemplateError>())
+        {
+            Some(syntax_error) => (
+                syntax_error.line_no,
+                syntax_error.column_no,
+                syntax_error.reason.to_string(),
+            ),
+            None => (err.line_no, err.column_no, err.desc.clone()),
+        };
+
+        TemplateError {
+            line,
+            column,
+            ..Self::new(
+                source,
+                if part.is_empty() {
+                    desc
+                } else {
+                    format!("in {}: {}", part, desc)
+                },
+            )
+        }
+    }
+
+    /// Takes the snippet from the content of the source, using the line of the error
+    pub(crate) fn with_snippet(mut self, content: &str) -> Self {
+        self.snippet = self
+            .line
+            .and_then(|line| content.lines().nth(line.saturating_sub(1)))
+            .map(|it| it.to_string());
+
+        self
+    }
+
+    fn from_io_error(source: &Path, err: &Error) -> Self {
+        match err
+            .get_ref()
+            .and_then(|it| it.downcast_ref::<RenderError>())
+        {
+            Some(render_error) => Self::from_render_error(source, "", render_error),
+            None => Self::new(source, err.to_string()),
+        }
+    }
+}
+
+impl Display for TemplateError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.source.display())?;
+
+        if let Some(line) = self.line {
+            write!(f, ":{}", line)?;
+
+            if let Some(column) = self.column {
+                write!(f, ":{}", column)?;
+            }
+        }
+
+        write!(f, ": {}", self.message)
+    }
 }
 
 #[derive(Debug)]
@@ -903,28 +1937,64 @@ pub struct RenderConflict {
 #[cfg(test)]
 #[allow(clippy::redundant_closure_call)]
 mod tests {
-    use std::fs::read_to_string;
+    use std::fs::{read_to_string, write};
 
+    use encoding_rs::WINDOWS_1252;
     use itertools::Itertools;
     use lazy_static::lazy_static;
     use serde_json::{Map, Number, Value};
-    use tempfile::{tempdir, TempDir};
+    use tempfile::tempdir;
 
-    use crate::config::Filters;
+    use crate::config::{
+        Conflicts, DataFile, DataFormat, FileMode, Filters, HelperSpec, Hooks, QuestionPath,
+        DEFAULT_PARTIALS_DIR,
+    };
     use crate::context::UnsafeContext;
     use crate::utils::glob;
     use crate::utils::tests::RESOURCES_DIR;
 
     use super::*;
 
+    fn test_tool_config() -> ToolConfig<'static> {
+        ToolConfig {
+            template: None,
+            no_history: false,
+            no_init: false,
+            ignore_checks: false,
+            dry_run: false,
+            verbose: false,
+            strict: false,
+            lint: false,
+            on_existing: None,
+            on_conflict: None,
+        }
+    }
+
+    /// Renders the source directory into the target directory without staging
+    fn render_dir(
+        source_dir: &Path,
+        target_dir: &Path,
+        config: &Config,
+        context: &Context,
+        tool_config: &ToolConfig,
+    ) -> io::Result<RenderResult> {
+        render(
+            source_dir,
+            target_dir,
+            target_dir,
+            config,
+            context,
+            tool_config,
+        )
+    }
+
     lazy_static! {
         static ref HANDLEBARS: Handlebars<'static> = (|| {
-            let mut handlebars = create_hbs();
+            let mut handlebars = create_hbs(false);
             handlebars.register_helper("package", Box::new(PACKAGE_HELPER));
 
             handlebars
         })();
-        static ref TEMP_DIR: TempDir = tempdir().unwrap();
     }
 
     #[test]
@@ -949,6 +2019,19 @@ mod tests {
         let config = Config {
             name: Some("Auto Template"),
             version: Some("0.x"),
+            data: vec![],
+            partials_dir: DEFAULT_PARTIALS_DIR,
+            helpers: vec![],
+            escape_modes: HashMap::new(),
+            strict: false,
+            conflicts: Conflicts {
+                strategy: ConflictStrategy::Concatenate,
+                priority: vec![],
+            },
+            hooks: Hooks::default(),
+            modes: vec![],
+            encodings: vec![],
+            delimiters: vec![],
             questions: vec![],
             filters: Filters {
                 include_hidden: vec![glob("**/*still-included*").unwrap()],
@@ -956,32 +2039,26 @@ mod tests {
                 conditional_files: vec![],
                 templates: None,
                 non_templates: Some(vec![glob("**/*.handlebars").unwrap()]),
+                raw: vec![],
             },
         };
 
         let tool_config = ToolConfig {
-            template: None,
-            no_history: false,
-            no_init: false,
-            ignore_checks: false,
-            dry_run: false,
             verbose: true,
+            ..test_tool_config()
         };
 
-        let render_result = render(&source_path, &target_path, &config, &context, &tool_config)?;
+        let render_result =
+            render_dir(&source_path, &target_path, &config, &context, &tool_config)?;
 
         assert_eq!(4, render_result.rendered_files.len());
-        assert_eq!(1, render_result.conflicts.len());
+        assert!(render_result.conflicts.is_empty());
 
-        let render_conflict = &render_result.conflicts[0];
+        let concatenated_content =
+            read_to_string(target_path.join("io/v47/test/file-in-generated-path.txt"))?;
 
-        assert_eq!(
-            target_path
-                .join("io/v47/test/file-in-generated-path.txt")
-                .absolutize()?,
-            render_conflict.intended_target
-        );
-        assert_eq!(2, render_conflict.sources.len());
+        assert!(concatenated_content.starts_with("Hello, this file is not a template"));
+        assert!(concatenated_content.ends_with("Hello Some dude!\n\nI'm in a generated path"));
 
         let override_template_path = target_path
             .join("templates/override-template.txt.handlebars")
@@ -989,17 +2066,13 @@ mod tests {
             .to_path_buf();
 
         assert!(override_template_path.exists());
-        assert!(is_hbs_template(&override_template_path)?);
+        assert!(is_hbs_template(&override_template_path, None, None)?);
 
         let sep = std::path::MAIN_SEPARATOR;
 
         let check_target_dir_content = vec![
             format!(".hidden-dir{}but-still-included.txt", sep),
             format!("io{}v47{}test{}file-in-explicit-path.txt", sep, sep, sep),
-            format!(
-                "io{}v47{}test{}file-in-generated-path (1).txt",
-                sep, sep, sep
-            ),
             format!("io{}v47{}test{}file-in-generated-path.txt", sep, sep, sep),
             format!("templates{}override-template.txt.handlebars", sep),
             format!("templates{}some-template.txt", sep),
@@ -1045,6 +2118,8 @@ mod tests {
                 source: source_path.join("abcdef.hbs"),
                 target: target_path.join("abcdef"),
                 is_template: true,
+                link_target: None,
+                front_matter: None,
             },
             &source_path,
         );
@@ -1107,6 +2182,16 @@ mod tests {
         let config = Config {
             name: Some("Auto Template"),
             version: Some("0.x"),
+            data: vec![],
+            partials_dir: DEFAULT_PARTIALS_DIR,
+            helpers: vec![],
+            escape_modes: HashMap::new(),
+            strict: false,
+            conflicts: Conflicts::default(),
+            hooks: Hooks::default(),
+            modes: vec![],
+            encodings: vec![],
+            delimiters: vec![],
             questions: vec![],
             filters: Filters {
                 include_hidden: vec![glob("**/*still-included*").unwrap()],
@@ -1114,34 +2199,36 @@ mod tests {
                 conditional_files: vec![],
                 templates: None,
                 non_templates: Some(vec![glob("**/some-template.txt.hbs").unwrap()]),
+                raw: vec![],
             },
         };
 
         let tool_config = ToolConfig {
-            template: None,
-            no_history: false,
-            no_init: false,
-            ignore_checks: false,
-            dry_run: false,
             verbose: true,
+            // Keeps all sources of the conflicting target
+            on_conflict: Some(ConflictStrategy::Concatenate),
+            ..test_tool_config()
         };
 
-        let render_specs = build_render_specs(
+        let (render_specs, conflicts) = build_render_specs(
             &source_path,
             &target_path,
             &config,
             &HANDLEBARS,
             &context,
             &tool_config,
+            &RefCell::new(vec![]),
         )?;
 
         // todo: actually verify result
         //       test other code paths
         println!("{:?}", render_specs);
 
+        assert!(conflicts.is_empty());
+
         let sep = std::path::MAIN_SEPARATOR;
 
-        let check_target_paths = vec![
+        let check_target_paths = [
             format!(".hidden-dir{}but-still-included.txt", sep),
             format!("io{}v47{}test{}file-in-explicit-path.txt", sep, sep, sep),
             format!("io{}v47{}test{}file-in-generated-path.txt", sep, sep, sep),
@@ -1176,6 +2263,119 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_resolve_conflicts() -> io::Result<()> {
+        let root_dir = tempdir()?;
+        let target_dir = root_dir.path().join("target");
+
+        write(root_dir.path().join("a.txt"), "a")?;
+        write(root_dir.path().join("b.txt"), "b")?;
+        write(root_dir.path().join("c.bin"), [0xff, 0xfe])?;
+
+        let render_spec = |name: &str, target: &str| RenderSpec {
+            source: root_dir.path().join(name),
+            target: target_dir.join(target),
+            is_template: false,
+            link_target: None,
+            front_matter: None,
+        };
+
+        let mut render_specs = HashMap::new();
+        render_specs.insert(
+            target_dir.join("text.txt"),
+            vec![
+                render_spec("a.txt", "text.txt"),
+                render_spec("b.txt", "text.txt"),
+            ],
+        );
+        render_specs.insert(
+            target_dir.join("binary.txt"),
+            vec![
+                render_spec("a.txt", "binary.txt"),
+                render_spec("c.bin", "binary.txt"),
+            ],
+        );
+        render_specs.insert(
+            target_dir.join("single.txt"),
+            vec![render_spec("a.txt", "single.txt")],
+        );
+
+        let tool_config = test_tool_config();
+
+        let resolve = |strategy: ConflictStrategy, priority: &[&str]| {
+            let mut config = Config::empty();
+            config.conflicts = Conflicts {
+                strategy,
+                priority: priority.iter().map(|it| glob(it).unwrap()).collect(),
+            };
+
+            resolve_conflicts(
+                render_specs.clone(),
+                root_dir.path(),
+                &target_dir,
+                &config,
+                &tool_config,
+            )
+            .unwrap()
+        };
+
+        let sources = |resolved: &HashMap<PathBuf, Vec<RenderSpec>>, target: &str| {
+            resolved.get(&target_dir.join(target)).map(|render_specs| {
+                render_specs
+                    .iter()
+                    .map(|it| it.source.file_name().unwrap().to_string_lossy().to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let (resolved, conflicts) = resolve(ConflictStrategy::Error, &[]);
+        assert_eq!(2, conflicts.len());
+        assert_eq!(target_dir.join("binary.txt"), conflicts[0].intended_target);
+        assert_eq!(
+            Some(vec!["a.txt".to_string()]),
+            sources(&resolved, "single.txt")
+        );
+        assert_eq!(None, sources(&resolved, "text.txt"));
+
+        let (resolved, conflicts) = resolve(ConflictStrategy::FirstWins, &[]);
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            Some(vec!["a.txt".to_string()]),
+            sources(&resolved, "text.txt")
+        );
+
+        let (resolved, conflicts) = resolve(ConflictStrategy::LastWins, &[]);
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            Some(vec!["c.bin".to_string()]),
+            sources(&resolved, "binary.txt")
+        );
+
+        let (resolved, conflicts) = resolve(ConflictStrategy::Concatenate, &[]);
+        assert_eq!(1, conflicts.len());
+        assert_eq!(
+            Some(vec!["a.txt".to_string(), "b.txt".to_string()]),
+            sources(&resolved, "text.txt")
+        );
+
+        let (resolved, conflicts) = resolve(ConflictStrategy::Priority, &["*.bin", "b.*"]);
+        assert!(conflicts.is_empty());
+        assert_eq!(
+            Some(vec!["b.txt".to_string()]),
+            sources(&resolved, "text.txt")
+        );
+        assert_eq!(
+            Some(vec!["c.bin".to_string()]),
+            sources(&resolved, "binary.txt")
+        );
+
+        let (_, conflicts) = resolve(ConflictStrategy::Priority, &["*.txt"]);
+        assert_eq!(1, conflicts.len());
+        assert_eq!(target_dir.join("text.txt"), conflicts[0].intended_target);
+
+        Ok(())
+    }
+
     #[test]
     fn test_include_dir_entry() -> io::Result<()> {
         let temp_root_dir = tempdir()?;
@@ -1184,15 +2384,28 @@ mod tests {
         let config = Config {
             name: None,
             version: None,
+            data: vec![],
+            partials_dir: DEFAULT_PARTIALS_DIR,
+            helpers: vec![HelperSpec {
+                name: "percent",
+                source: HelperSource::Script("helpers/percent.rhai"),
+            }],
+            escape_modes: HashMap::new(),
+            strict: false,
+            conflicts: Conflicts::default(),
+            hooks: Hooks::default(),
+            modes: vec![],
+            encodings: vec![],
+            delimiters: vec![],
             questions: vec![],
             filters: Filters {
                 conditional_files: vec![
                     ConditionalFilesSpec {
-                        condition: "someValue",
+                        condition: Condition::Handlebars("someValue"),
                         matcher: glob("matched_file").unwrap(),
                     },
                     ConditionalFilesSpec {
-                        condition: "not someValue",
+                        condition: Condition::Handlebars("not someValue"),
                         matcher: glob("unmatched_file").unwrap(),
                     },
                 ],
@@ -1200,6 +2413,7 @@ mod tests {
                 exclude: vec![glob("excluded_file").unwrap()],
                 templates: None,
                 non_templates: None,
+                raw: vec![],
             },
         };
 
@@ -1209,14 +2423,12 @@ mod tests {
         let context = UnsafeContext::new(context_map).into();
 
         let tool_config = ToolConfig {
-            template: None,
             verbose: true,
-            ignore_checks: false,
-            no_history: false,
-            dry_run: false,
-            no_init: false,
+            ..test_tool_config()
         };
 
+        let errors = RefCell::new(vec![]);
+
         assert!(!include_dir_entry(
             &root_dir.join(".git"),
             true,
@@ -1225,6 +2437,7 @@ mod tests {
             &HANDLEBARS,
             &context,
             &tool_config,
+            &errors,
         ));
 
         assert!(!include_dir_entry(
@@ -1235,6 +2448,7 @@ mod tests {
             &HANDLEBARS,
             &context,
             &tool_config,
+            &errors,
         ));
 
         assert!(include_dir_entry(
@@ -1245,6 +2459,7 @@ mod tests {
             &HANDLEBARS,
             &context,
             &tool_config,
+            &errors,
         ));
 
         assert!(include_dir_entry(
@@ -1255,6 +2470,7 @@ mod tests {
             &HANDLEBARS,
             &context,
             &tool_config,
+            &errors,
         ));
 
         assert!(!include_dir_entry(
@@ -1265,6 +2481,7 @@ mod tests {
             &HANDLEBARS,
             &context,
             &tool_config,
+            &errors,
         ));
 
         assert!(!include_dir_entry(
@@ -1275,27 +2492,132 @@ mod tests {
             &HANDLEBARS,
             &context,
             &tool_config,
+            &errors,
+        ));
+
+        assert!(!include_dir_entry(
+            &root_dir.join(DEFAULT_PARTIALS_DIR),
+            true,
+            root_dir,
+            &config,
+            &HANDLEBARS,
+            &context,
+            &tool_config,
+            &errors,
+        ));
+
+        assert!(!include_dir_entry(
+            &root_dir.join("helpers/percent.rhai"),
+            false,
+            root_dir,
+            &config,
+            &HANDLEBARS,
+            &context,
+            &tool_config,
+            &errors,
+        ));
+
+        assert!(include_dir_entry(
+            &root_dir.join("helpers/README.md"),
+            false,
+            root_dir,
+            &config,
+            &HANDLEBARS,
+            &context,
+            &tool_config,
+            &errors,
         ));
 
         Ok(())
     }
 
+    #[test]
+    fn test_register_partials() -> io::Result<()> {
+        let temp_root_dir = tempdir()?;
+        let root_dir = temp_root_dir.path();
+
+        let partials_dir = root_dir.join(DEFAULT_PARTIALS_DIR);
+        create_dir_all(partials_dir.join("license"))?;
+        write(
+            partials_dir.join("license/header.hbs"),
+            "// Copyright {{author}}",
+        )?;
+        write(partials_dir.join("footer.handlebars"), "// The end")?;
+
+        let tool_config = ToolConfig {
+            no_history: true,
+            no_init: true,
+            ..test_tool_config()
+        };
+
+        let mut handlebars = create_hbs(false);
+        register_partials(&mut handlebars, root_dir, &Config::empty(), &tool_config)?;
+
+        let mut context_map = Map::new();
+        context_map.insert("author".into(), Value::String("Some dude!".into()));
+        let context = UnsafeContext::new(context_map).into();
+
+        assert_eq!(
+            "// Copyright Some dude! // The end",
+            handlebars
+                .render_template_with_context("{{> license/header}} {{> footer}}", &context)
+                .unwrap()
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_is_hbs_template() -> io::Result<()> {
         let template_file = RESOURCES_DIR.join("simple-template.input/simple-template.html.hbs");
 
-        assert!(is_hbs_template(&template_file)?);
+        assert!(is_hbs_template(&template_file, None, None)?);
 
         let non_template_file =
             RESOURCES_DIR.join("simple-template.expected/en/simple-template.html");
 
-        assert!(!is_hbs_template(&non_template_file)?);
+        assert!(!is_hbs_template(&non_template_file, None, None)?);
+
+        let dir = tempdir()?;
+
+        let binary_file = dir.path().join("image.png");
+        write(&binary_file, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR{{ \xFF")?;
+        assert!(!is_hbs_template(&binary_file, None, None)?);
+
+        let latin_file = dir.path().join("messages.properties");
+        write(&latin_file, b"greeting=Gr\xFC\xDFe {{ name }}")?;
+        assert!(!is_hbs_template(&latin_file, None, None)?);
+        assert!(is_hbs_template(&latin_file, Some(WINDOWS_1252), None)?);
+
+        let marked_file = dir.path().join("config.yml.HBS");
+        write(&marked_file, "no expressions")?;
+        assert!(is_hbs_template(&marked_file, None, None)?);
+
+        let late_file = dir.path().join("config.yml");
+        write(&late_file, "line\n".repeat(40) + "{{#if a\n}}{{/if}}")?;
+        assert!(is_hbs_template(&late_file, None, None)?);
+
+        let vue_file = dir.path().join("App.vue");
+        write(&vue_file, "<p>{{ message }}</p>")?;
+        let delimiters = Delimiters {
+            matcher: glob("**/*.vue").unwrap(),
+            start: "<%",
+            end: "%>",
+        };
+        assert!(is_hbs_template(&vue_file, None, None)?);
+        assert!(!is_hbs_template(&vue_file, None, Some(&delimiters))?);
+        write(&vue_file, "<p>{{ message }} <% name %></p>")?;
+        assert!(is_hbs_template(&vue_file, None, Some(&delimiters))?);
+
+        let hidden_file = dir.path().join(".hbs");
+        write(&hidden_file, "no expressions")?;
+        assert!(!is_hbs_template(&hidden_file, None, None)?);
 
         Ok(())
     }
 
     #[test]
-    fn test_render_to_file() {
+    fn test_render_template() -> io::Result<()> {
         let mut context_map = Map::new();
         context_map.insert("lang".into(), Value::String("en".into()));
 
@@ -1314,17 +2636,68 @@ mod tests {
         let context = UnsafeContext::new(context_map).into();
 
         let source_path = RESOURCES_DIR.join("simple-template.input/simple-template.html.hbs");
-        let target_path = TEMP_DIR.path().join("simple-template.html");
 
-        render_template_to_file(&source_path, &target_path, &HANDLEBARS, &context).unwrap();
+        let en_actual_content = render_template(
+            &source_path,
+            &HANDLEBARS,
+            &context,
+            EscapeMode::Html,
+            None,
+            None,
+            0,
+        )
+        .unwrap();
 
         let en_expected_content =
             read_to_string(RESOURCES_DIR.join("simple-template.expected/en/simple-template.html"))
                 .unwrap();
 
-        let en_actual_content = read_to_string(target_path).unwrap();
+        assert_eq!(en_expected_content.into_bytes(), en_actual_content);
+
+        let dir = tempdir()?;
+        let latin_file = dir.path().join("messages.properties");
+        write(&latin_file, b"greeting=Gr\xFC\xDFe {{ lang }}")?;
 
-        assert_eq!(en_expected_content, en_actual_content);
+        let latin_actual_content = render_template(
+            &latin_file,
+            &HANDLEBARS,
+            &context,
+            EscapeMode::None,
+            Some(WINDOWS_1252),
+            None,
+            0,
+        )?;
+
+        assert_eq!(b"greeting=Gr\xFC\xDFe en".to_vec(), latin_actual_content);
+
+        let vue_file = dir.path().join("App.vue");
+        write(
+            &vue_file,
+            "<p :lang=\"'<% lang %>'\">{{ message }} {{{{raw}}}}</p><%#if lang%>!<%/if%>",
+        )?;
+
+        let delimiters = Delimiters {
+            matcher: glob("**/*.vue").unwrap(),
+            start: "<%",
+            end: "%>",
+        };
+
+        let vue_actual_content = render_template(
+            &vue_file,
+            &HANDLEBARS,
+            &context,
+            EscapeMode::None,
+            None,
+            Some(&delimiters),
+            0,
+        )?;
+
+        assert_eq!(
+            "<p :lang=\"'en'\">{{ message }} {{{{raw}}}}</p>!",
+            String::from_utf8(vue_actual_content).unwrap()
+        );
+
+        Ok(())
     }
 
     #[test]
@@ -1337,7 +2710,7 @@ mod tests {
 
         assert!(eval_condition(
             &ConditionalFilesSpec {
-                condition: "simple",
+                condition: Condition::Handlebars("simple"),
                 matcher: glob("").unwrap(),
             },
             &HANDLEBARS,
@@ -1347,84 +2720,527 @@ mod tests {
 
         assert!(!eval_condition(
             &ConditionalFilesSpec {
-                condition: "falsy",
+                condition: Condition::Handlebars("falsy"),
                 matcher: glob("").unwrap(),
             },
             &HANDLEBARS,
             &context,
         )
         .unwrap_or(false));
+
+        assert!(eval_condition(
+            &ConditionalFilesSpec {
+                condition: Condition::Expression(
+                    "simple == 2 && !falsy",
+                    Expression::parse("simple == 2 && !falsy").unwrap()
+                ),
+                matcher: glob("").unwrap(),
+            },
+            &HANDLEBARS,
+            &context,
+        )
+        .unwrap_or(false));
+
+        assert!(eval_condition(
+            &ConditionalFilesSpec {
+                condition: Condition::Expression("simple", Expression::parse("simple").unwrap()),
+                matcher: glob("").unwrap(),
+            },
+            &HANDLEBARS,
+            &context,
+        )
+        .is_err());
     }
 
     #[test]
-    fn test_render_line_template() {
+    fn test_render_strict() -> io::Result<()> {
+        let source_dir = tempdir()?;
+        let target_dir = tempdir()?;
+
+        write(
+            source_dir.path().join("content.txt"),
+            "{{ author }}\nHello {{ missing }}\n{{ also.missing }} {{ shout author }} {{#if undefined }}!{{/if}}\n",
+        )?;
+
         let mut context_map = Map::new();
-        context_map.insert("newLine".into(), Value::String("multi\n  \nline".into()));
-        context_map.insert("simple".into(), Value::Number(Number::from(2)));
+        context_map.insert("author".into(), Value::String("Some dude!".into()));
 
         let context = UnsafeContext::new(context_map).into();
 
+        let mut config = Config::empty();
+        config.strict = true;
+
+        let tool_config = test_tool_config();
+
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &config,
+            &context,
+            &tool_config,
+        )?;
+
+        // Every undefined reference is reported, not only the first one Handlebars stops at
+        assert!(render_result.rendered_files.is_empty());
+        assert_eq!(
+            vec![
+                "content.txt:2:7: Variable \"missing\" not found in strict mode.",
+                "content.txt:3:1: Variable \"also.missing\" not found in strict mode.",
+                "content.txt:3:20: Helper not defined: \"shout\"",
+            ],
+            render_result
+                .errors
+                .iter()
+                .map(|it| it.to_string())
+                .collect::<Vec<_>>()
+        );
         assert_eq!(
-            ("multi line".into(), true),
-            render_line_template("{{ newLine }}", &HANDLEBARS, &context)
+            Some("Hello {{ missing }}"),
+            render_result.errors[0].snippet.as_deref()
         );
+        assert!(!target_dir.path().join("content.txt").exists());
+
+        write(source_dir.path().join("{{ nothing }}-{{ none }}.txt"), "")?;
 
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &config,
+            &context,
+            &tool_config,
+        )?;
+
+        // Nothing is rendered at all if a name contains an undefined reference
         assert_eq!(
-            ("{{ asdasd".into(), false),
-            render_line_template("{{ asdasd", &HANDLEBARS, &context)
+            vec![
+                "{{ nothing }}-{{ none }}.txt:1:1: in name: Variable \"nothing\" not found in strict mode.",
+                "{{ nothing }}-{{ none }}.txt:1:15: in name: Variable \"none\" not found in strict mode.",
+            ],
+            render_result
+                .errors
+                .iter()
+                .map(|it| it.to_string())
+                .collect::<Vec<_>>()
         );
+        assert!(!target_dir
+            .path()
+            .join("{{ nothing }}-{{ none }}.txt")
+            .exists());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_condition_errors() -> io::Result<()> {
+        let source_dir = tempdir()?;
+        let target_dir = tempdir()?;
+
+        write(source_dir.path().join("many.txt"), "many")?;
+        write(
+            source_dir.path().join("few.txt"),
+            "--- # architect\nwhen: size == 'few'\n---\nfew",
+        )?;
+
+        let mut config = Config::empty();
+        config.filters.conditional_files = vec![ConditionalFilesSpec {
+            condition: Condition::Expression(
+                "size == 'many'",
+                Expression::parse("size == 'many'").unwrap(),
+            ),
+            matcher: glob("many.txt").unwrap(),
+        }];
+
+        // The conditions fail, because size isn't in the context
+        let context = UnsafeContext::new(Map::new()).into();
 
+        // Files whose conditions fail are skipped with a warning
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &config,
+            &context,
+            &test_tool_config(),
+        )?;
+
+        assert!(render_result.errors.is_empty());
+        assert!(!target_dir.path().join("many.txt").exists());
+        assert!(!target_dir.path().join("few.txt").exists());
+
+        let tool_config = ToolConfig {
+            strict: true,
+            ..test_tool_config()
+        };
+
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &config,
+            &context,
+            &tool_config,
+        )?;
+
+        let mut sources: Vec<_> = render_result
+            .errors
+            .iter()
+            .map(|error| error.source.clone())
+            .collect();
+        sources.sort();
         assert_eq!(
-            ("2".into(), true),
-            render_line_template("{{ simple }}", &HANDLEBARS, &context)
+            vec![PathBuf::from("few.txt"), PathBuf::from("many.txt")],
+            sources
         );
+
+        Ok(())
     }
 
     #[test]
-    fn test_create_proper_target_path() {
-        let temp_dir = tempdir().unwrap();
+    fn test_render_errors() -> io::Result<()> {
+        let source_dir = tempdir()?;
+        let target_dir = tempdir()?;
 
-        let target_dir = temp_dir.path().join("some-target-dir");
+        write(source_dir.path().join("broken.txt"), "{{ a }}\n{{ b }\n")?;
+        write(source_dir.path().join("fine.txt"), "{{ a }}")?;
+
+        let tool_config = test_tool_config();
+
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &Config::empty(),
+            &UnsafeContext::new(Map::new()).into(),
+            &tool_config,
+        )?;
+
+        assert_eq!(1, render_result.rendered_files.len());
+        assert_eq!(1, render_result.errors.len());
+
+        let error = &render_result.errors[0];
+        assert_eq!(Path::new("broken.txt"), error.source);
+        assert_eq!(Some(2), error.line);
+        assert_eq!(Some("{{ b }"), error.snippet.as_deref());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_data_files() -> io::Result<()> {
+        let source_dir = tempdir()?;
+        let target_dir = tempdir()?;
+
+        create_dir_all(source_dir.path().join("data"))?;
+        write(
+            source_dir.path().join("data/regions.yaml"),
+            "eu: https://eu.example.com\n",
+        )?;
+        write(source_dir.path().join("data/README.md"), "{{ name }}")?;
+
+        let mut config = Config::empty();
+        config.data = vec![DataFile {
+            path: QuestionPath::parse("regions").unwrap(),
+            file: "data/regions.yaml",
+            format: DataFormat::Yaml,
+        }];
+
+        let mut context_map = Map::new();
+        context_map.insert("name".into(), Value::String("data".into()));
 
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &config,
+            &UnsafeContext::new(context_map).into(),
+            &test_tool_config(),
+        )?;
+
+        assert!(render_result.errors.is_empty());
+        assert!(!target_dir.path().join("data/regions.yaml").exists());
         assert_eq!(
-            Some(target_dir.join("this-is-a-file-name")),
-            create_proper_target_path(temp_dir.path(), &target_dir, "this-is-a-file-name")
+            "data",
+            read_to_string(target_dir.path().join("data/README.md"))?
         );
 
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_raw_files() -> io::Result<()> {
+        let source_dir = tempdir()?;
+        let target_dir = tempdir()?;
+
+        create_dir_all(source_dir.path().join("workflows"))?;
+        write(
+            source_dir.path().join("workflows/ci.yml.hbs"),
+            "run: echo ${{ github.sha }}",
+        )?;
+        write(source_dir.path().join("README.md"), "{{ name }}")?;
+
+        let mut config = Config::empty();
+        config.filters.raw = vec![glob("workflows/**").unwrap()];
+
+        let mut context_map = Map::new();
+        context_map.insert("name".into(), Value::String("raw".into()));
+
+        let tool_config = test_tool_config();
+
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &config,
+            &UnsafeContext::new(context_map).into(),
+            &tool_config,
+        )?;
+
+        assert!(render_result.errors.is_empty());
         assert_eq!(
-            None,
-            create_proper_target_path(temp_dir.path(), &target_dir, "../../abcdef")
+            "run: echo ${{ github.sha }}",
+            read_to_string(target_dir.path().join("workflows/ci.yml.hbs"))?
         );
+        assert_eq!("raw", read_to_string(target_dir.path().join("README.md"))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_front_matter() -> io::Result<()> {
+        let source_dir = tempdir()?;
+        let target_dir = tempdir()?;
+
+        write(
+            source_dir.path().join("service.txt"),
+            "--- # architect\ntarget: src/{{ name }}/Service.java\nmode: 700\n---\nclass {{ name }}\n",
+        )?;
+        write(
+            source_dir.path().join("Dockerfile"),
+            "--- # architect\nwhen: useDocker\n---\nFROM {{ name }}\n",
+        )?;
+        write(
+            source_dir.path().join("keep.txt"),
+            "--- # architect\noverwrite: skip\n---\nnew\n",
+        )?;
+        write(target_dir.path().join("keep.txt"), "old\n")?;
+        write(
+            source_dir.path().join("escape.txt"),
+            "--- # architect\ntarget: ../{{ name }}.txt\n---\n",
+        )?;
+        write(
+            source_dir.path().join("broken.txt"),
+            "--- # architect\nmode: 644\n---\nline\n{{#if}}\n",
+        )?;
+
+        // Front matter of other tools uses the same keys, but isn't marked for Architect
+        let tsconfig = "---\ntarget: es2015\nmodule: commonjs\n---\n";
+        write(source_dir.path().join("tsconfig.yml"), tsconfig)?;
+        let page = "---\nlayout: page\nwhen: 2021-10-18\n---\n# Welcome\n";
+        write(source_dir.path().join("index.md"), page)?;
 
+        let mut context_map = Map::new();
+        context_map.insert("name".into(), Value::String("app".into()));
+        context_map.insert("useDocker".into(), Value::Bool(false));
+
+        let tool_config = test_tool_config();
+
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &Config::empty(),
+            &UnsafeContext::new(context_map).into(),
+            &tool_config,
+        )?;
+
+        let service = target_dir.path().join("src/app/Service.java");
+        assert_eq!("class app\n", read_to_string(&service)?);
+        assert!(!target_dir.path().join("service.txt").exists());
+        assert!(!target_dir.path().join("Dockerfile").exists());
+        assert_eq!("old\n", read_to_string(target_dir.path().join("keep.txt"))?);
         assert_eq!(
-            None,
-            create_proper_target_path(temp_dir.path(), temp_dir.path(), &"../".repeat(15))
+            tsconfig,
+            read_to_string(target_dir.path().join("tsconfig.yml"))?
         );
+        assert_eq!(page, read_to_string(target_dir.path().join("index.md"))?);
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            assert_eq!(0o700, metadata(&service)?.permissions().mode() & 0o7777);
+        }
+
+        let mut errors: Vec<_> = render_result
+            .errors
+            .iter()
+            .map(|error| (error.source.clone(), error.line))
+            .collect();
+        errors.sort();
+        assert_eq!(
+            vec![
+                (PathBuf::from("broken.txt"), Some(6)),
+                (PathBuf::from("escape.txt"), None),
+            ],
+            errors
+        );
+
+        Ok(())
     }
 
+    #[cfg(unix)]
     #[test]
-    fn test_get_numbered_path() {
-        let path_with_parent = PathBuf::from("some/path.xml");
-        let path_without_extension = PathBuf::from("just-a-random-file");
+    fn test_render_file_modes() -> io::Result<()> {
+        use std::os::unix::fs::PermissionsExt;
+
+        let source_dir = tempdir()?;
+        let target_dir = tempdir()?;
+
+        write(source_dir.path().join("run.sh"), "echo {{ a }}")?;
+        write(source_dir.path().join("gradlew"), "echo {{ a }}")?;
+        write(source_dir.path().join("README.md"), "Plain")?;
+        set_permissions(
+            source_dir.path().join("run.sh"),
+            Permissions::from_mode(0o755),
+        )?;
+        set_permissions(
+            source_dir.path().join("gradlew"),
+            Permissions::from_mode(0o644),
+        )?;
+        set_permissions(
+            source_dir.path().join("README.md"),
+            Permissions::from_mode(0o600),
+        )?;
+
+        let mut config = Config::empty();
+        config.modes = vec![FileMode {
+            matcher: glob("gradlew").unwrap(),
+            mode: 0o750,
+        }];
+
+        let tool_config = test_tool_config();
+
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &config,
+            &UnsafeContext::new(Map::new()).into(),
+            &tool_config,
+        )?;
+
+        assert!(render_result.errors.is_empty());
+
+        let mode = |name: &str| -> io::Result<u32> {
+            Ok(metadata(target_dir.path().join(name))?.permissions().mode() & 0o7777)
+        };
+
+        assert_eq!(0o755, mode("run.sh")?);
+        assert_eq!(0o750, mode("gradlew")?);
+        assert_eq!(0o600, mode("README.md")?);
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_render_symlinks() -> io::Result<()> {
+        use std::os::unix::fs::symlink;
+
+        let source_dir = tempdir()?;
+
+        create_dir_all(source_dir.path().join("docs"))?;
+        create_dir_all(source_dir.path().join("1.0"))?;
+        write(source_dir.path().join("README.md"), "{{ version }}")?;
+        symlink("../README.md", source_dir.path().join("docs/README.md"))?;
+        symlink("{{ version }}", source_dir.path().join("current"))?;
+
+        let mut context_map = Map::new();
+        context_map.insert("version".into(), Value::String("1.0".into()));
+        let context = UnsafeContext::new(context_map).into();
+
+        let tool_config = test_tool_config();
+
+        let target_dir = tempdir()?;
+
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &Config::empty(),
+            &context,
+            &tool_config,
+        )?;
+
+        assert!(render_result.errors.is_empty());
+        assert_eq!(
+            Path::new("../README.md"),
+            read_link(target_dir.path().join("docs/README.md"))?
+        );
+        assert_eq!(
+            "1.0",
+            read_to_string(target_dir.path().join("docs/README.md"))?
+        );
+        assert_eq!(
+            Path::new("1.0"),
+            read_link(target_dir.path().join("current"))?
+        );
+
+        symlink("../../outside", source_dir.path().join("docs/escaping"))?;
+
+        let target_dir = tempdir()?;
+
+        let render_result = render_dir(
+            source_dir.path(),
+            target_dir.path(),
+            &Config::empty(),
+            &context,
+            &tool_config,
+        )?;
+
+        assert_eq!(1, render_result.errors.len());
+        assert_eq!(Path::new("docs/escaping"), render_result.errors[0].source);
+        assert!(target_dir
+            .path()
+            .join("docs/escaping")
+            .symlink_metadata()
+            .is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_line_template() {
+        let mut context_map = Map::new();
+        context_map.insert("newLine".into(), Value::String("multi\n  \nline".into()));
+        context_map.insert("simple".into(), Value::Number(Number::from(2)));
+
+        let context = UnsafeContext::new(context_map).into();
 
         assert_eq!(
-            PathBuf::from("some/path (2).xml"),
-            get_numbered_path(path_with_parent.clone(), 2)
+            "multi line",
+            render_line_template("{{ newLine }}", &HANDLEBARS, &context).unwrap()
         );
 
+        assert!(render_line_template("{{ asdasd", &HANDLEBARS, &context).is_err());
+
+        assert_eq!(
+            "2",
+            render_line_template("{{ simple }}", &HANDLEBARS, &context).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_create_proper_target_path() {
+        let temp_dir = tempdir().unwrap();
+
+        let target_dir = temp_dir.path().join("some-target-dir");
+
         assert_eq!(
-            PathBuf::from("just-a-random-file (11)"),
-            get_numbered_path(path_without_extension.clone(), 11)
+            Some(target_dir.join("this-is-a-file-name")),
+            create_proper_target_path(temp_dir.path(), &target_dir, "this-is-a-file-name")
         );
 
         assert_eq!(
-            PathBuf::from("some/path.xml"),
-            get_numbered_path(path_with_parent, 0)
+            None,
+            create_proper_target_path(temp_dir.path(), &target_dir, "../../abcdef")
         );
 
         assert_eq!(
-            PathBuf::from("just-a-random-file"),
-            get_numbered_path(path_without_extension, 0)
+            None,
+            create_proper_target_path(temp_dir.path(), temp_dir.path(), &"../".repeat(15))
         );
     }
 }
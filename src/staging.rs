@@ -0,0 +1,270 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::fs::{create_dir_all, read_dir, remove_dir, remove_dir_all, remove_file, rename};
+use std::io;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::process::{self, exit};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// A directory next to the target directory that receives all output first, so the target
+/// directory is only populated once generation succeeded.
+///
+/// Unless committed, the staging directory is removed again when dropped or on Ctrl-C, leaving
+/// the target directory untouched.
+pub struct StagingDir {
+    path: PathBuf,
+    target_dir: PathBuf,
+    committed: bool,
+    /// Whether the Ctrl-C handler removes the staging directory, it can't be unregistered
+    armed: Arc<AtomicBool>,
+}
+
+impl StagingDir {
+    pub fn create(target_dir: &Path) -> io::Result<StagingDir> {
+        let parent = target_dir.parent().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("Target directory {} has no parent", target_dir.display()),
+            )
+        })?;
+
+        let path = parent.join(format!(
+            ".{}.architect-{}",
+            target_dir.file_name().unwrap().to_string_lossy(),
+            process::id()
+        ));
+
+        create_dir_all(&path)?;
+
+        let staging_dir = StagingDir {
+            path,
+            target_dir: target_dir.to_path_buf(),
+            committed: false,
+            armed: Arc::new(AtomicBool::new(true)),
+        };
+
+        let path = staging_dir.path.clone();
+        let armed = staging_dir.armed.clone();
+        ctrlc::set_handler(move || {
+            if armed.load(Ordering::SeqCst) {
+                let _ = remove_dir_all(&path);
+            }
+
+            // 128 + SIGINT, like shells report it
+            exit(130);
+        })
+        .map_err(Error::other)?;
+
+        Ok(staging_dir)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Moves the staging directory into place. If the target directory already has content, the
+    /// staged files are moved into it, replacing existing files.
+    ///
+    /// Files can't be moved back once they replaced existing ones, so if merging fails, the files
+    /// that weren't moved yet are kept in the staging directory and the error lists the moved ones
+    pub fn commit(mut self) -> io::Result<()> {
+        if !self.target_dir.exists() {
+            rename(&self.path, &self.target_dir)?;
+        } else if read_dir(&self.target_dir)?.next().is_none() {
+            remove_dir(&self.target_dir)?;
+            rename(&self.path, &self.target_dir)?;
+        } else {
+            let mut moved = vec![];
+
+            if let Err(err) = merge_dir(&self.path, &self.target_dir, &mut moved) {
+                self.committed = true;
+                self.armed.store(false, Ordering::SeqCst);
+
+                return Err(self.merge_error(err, &moved));
+            }
+
+            remove_dir_all(&self.path)?;
+        }
+
+        self.committed = true;
+        self.armed.store(false, Ordering::SeqCst);
+
+        Ok(())
+    }
+
+    fn merge_error(&self, err: Error, moved: &[PathBuf]) -> Error {
+        let moved = if moved.is_empty() {
+            " none".to_string()
+        } else {
+            moved
+                .iter()
+                .map(|it| {
+                    format!(
+                        "\n  - {}",
+                        it.strip_prefix(&self.target_dir).unwrap().display()
+                    )
+                })
+                .collect()
+        };
+
+        Error::new(
+            err.kind(),
+            format!(
+                "Failed to move the generated files into {} ({}), the remaining ones are kept in {}. Files already moved:{}",
+                self.target_dir.display(),
+                err,
+                self.path.display(),
+                moved
+            ),
+        )
+    }
+}
+
+/// Moves the entries of the source directory into the target directory in order, recording the
+/// targets that were moved
+fn merge_dir(source_dir: &Path, target_dir: &Path, moved: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = read_dir(source_dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|it| it.file_name());
+
+    for entry in entries {
+        let target = target_dir.join(entry.file_name());
+
+        if entry.file_type()?.is_dir() && target.is_dir() {
+            merge_dir(&entry.path(), &target, moved)?;
+        } else {
+            if target.is_file() {
+                remove_file(&target)?;
+            }
+
+            rename(entry.path(), &target)?;
+            moved.push(target);
+        }
+    }
+
+    Ok(())
+}
+
+impl Drop for StagingDir {
+    fn drop(&mut self) {
+        if !self.committed {
+            self.armed.store(false, Ordering::SeqCst);
+            let _ = remove_dir_all(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::{read_to_string, write};
+
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_staging_dir() -> io::Result<()> {
+        let base_dir = tempdir()?;
+        let target_dir = base_dir.path().join("project");
+        create_dir_all(&target_dir)?;
+
+        let staging_dir = StagingDir::create(&target_dir)?;
+        let staging_path = staging_dir.path().to_path_buf();
+        assert!(staging_path.is_dir());
+        assert_eq!(Some(base_dir.path()), staging_path.parent());
+
+        write(staging_path.join("file.txt"), "rolled back")?;
+        drop(staging_dir);
+
+        assert!(!staging_path.exists());
+        assert!(target_dir.is_dir());
+
+        // The Ctrl-C handler can only be registered once per process
+        let staging_dir = StagingDir {
+            path: staging_path.clone(),
+            target_dir: target_dir.clone(),
+            committed: false,
+            armed: Arc::new(AtomicBool::new(true)),
+        };
+        create_dir_all(staging_dir.path())?;
+
+        write(staging_path.join("file.txt"), "committed")?;
+        staging_dir.commit()?;
+
+        assert!(!staging_path.exists());
+        assert!(target_dir.join("file.txt").is_file());
+
+        let staging_dir = StagingDir {
+            path: staging_path.clone(),
+            target_dir: target_dir.clone(),
+            committed: false,
+            armed: Arc::new(AtomicBool::new(true)),
+        };
+        create_dir_all(staging_path.join("sub"))?;
+        create_dir_all(target_dir.join("sub"))?;
+        write(target_dir.join("sub/kept.txt"), "kept")?;
+
+        write(staging_path.join("file.txt"), "merged")?;
+        write(staging_path.join("sub/new.txt"), "merged")?;
+        staging_dir.commit()?;
+
+        assert!(!staging_path.exists());
+        assert_eq!("merged", read_to_string(target_dir.join("file.txt"))?);
+        assert_eq!("merged", read_to_string(target_dir.join("sub/new.txt"))?);
+        assert_eq!("kept", read_to_string(target_dir.join("sub/kept.txt"))?);
+
+        // A file can't replace a directory, so merging fails after moving the first file
+        let staging_dir = StagingDir {
+            path: staging_path.clone(),
+            target_dir: target_dir.clone(),
+            committed: false,
+            armed: Arc::new(AtomicBool::new(true)),
+        };
+        let armed = staging_dir.armed.clone();
+        create_dir_all(&staging_path)?;
+        write(staging_path.join("file.txt"), "moved")?;
+        write(staging_path.join("sub"), "not a directory")?;
+
+        let err = staging_dir.commit().unwrap_err();
+
+        assert!(err
+            .to_string()
+            .ends_with("Files already moved:\n  - file.txt"));
+        assert_eq!("moved", read_to_string(target_dir.join("file.txt"))?);
+        assert!(staging_path.join("sub").is_file());
+        assert!(!armed.load(Ordering::SeqCst));
+
+        Ok(())
+    }
+}
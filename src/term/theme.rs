@@ -60,7 +60,7 @@ impl PromptPunctuation for str {
     fn has_punctuation(&self) -> bool {
         static CHARS: [char; 2] = [':', '?'];
 
-        if let Some(last) = self.chars().rev().next() {
+        if let Some(last) = self.chars().next_back() {
             CHARS.contains(&last)
         } else {
             false
@@ -0,0 +1,246 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::fs::{create_dir_all, read_to_string, write};
+use std::io;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+
+use crossterm::style::Stylize;
+use dialoguer::Confirm;
+use serde_json::{Map, Value};
+
+use crate::config::{Config, HelperSource};
+use crate::dirs::data_file;
+
+/// Makes sure the user trusts the template before any of its commands can run. Returns whether
+/// the commands may run.
+///
+/// Trust is remembered for the commit of a template source, templates without a known commit
+/// have to be confirmed every time
+pub fn ensure_trusted(
+    config: &Config,
+    source: &str,
+    commit: Option<&str>,
+    pre_approved: bool,
+) -> io::Result<bool> {
+    let commands = template_commands(config);
+    if commands.is_empty() {
+        return Ok(true);
+    }
+
+    if pre_approved {
+        println!("{}", "Trusting the template (--trust)".dim());
+        return Ok(true);
+    }
+
+    let trust_file = trust_file();
+
+    if let (Some(trust_file), Some(commit)) = (&trust_file, commit) {
+        if is_trusted_in(trust_file, source, commit)? {
+            return Ok(true);
+        }
+    }
+
+    println!("This template wants to run these commands:");
+    commands
+        .iter()
+        .for_each(|command| println!("  - {}", command.as_str().yellow()));
+
+    if !io::stdin().is_terminal() {
+        eprintln!(
+            "{}",
+            "Refusing to run the commands of an untrusted template in non-interactive mode (use --trust to allow them)"
+                .red()
+        );
+
+        return Ok(false);
+    }
+
+    let trusted = Confirm::with_theme(&crate::term::theme::INSTANCE)
+        .with_prompt("Do you trust this template and allow these commands?")
+        .default(false)
+        .interact()?;
+
+    if let (true, Some(trust_file), Some(commit)) = (trusted, &trust_file, commit) {
+        if let Err(err) = store_trust_into(trust_file, source, commit) {
+            eprintln!(
+                "{}",
+                format!(
+                    "{:#}",
+                    anyhow::Error::from(err).context("Failed to remember trusted template")
+                )
+                .dark_yellow()
+            );
+        }
+    }
+
+    Ok(trusted)
+}
+
+/// Descriptions of all commands the template can run, i.e. command helpers and hooks
+fn template_commands(config: &Config) -> Vec<String> {
+    let helpers = config
+        .helpers
+        .iter()
+        .filter_map(|helper| match &helper.source {
+            HelperSource::Command { command, .. } => {
+                Some(format!("Helper '{}': {}", helper.name, command.join(" ")))
+            }
+            HelperSource::Script(_) => None,
+        });
+
+    let pre_generate_hooks = config
+        .hooks
+        .pre_generate
+        .iter()
+        .map(|hook| format!("Pre-generate hook: {}", hook.command.join(" ")));
+
+    let post_generate_hooks = config
+        .hooks
+        .post_generate
+        .iter()
+        .map(|hook| format!("Post-generate hook: {}", hook.command.join(" ")));
+
+    helpers
+        .chain(pre_generate_hooks)
+        .chain(post_generate_hooks)
+        .collect()
+}
+
+fn trust_file() -> Option<PathBuf> {
+    data_file("trust.json")
+}
+
+fn read_trust_file(trust_file: &Path) -> io::Result<Map<String, Value>> {
+    if trust_file.is_file() {
+        Ok(serde_json::from_str(&read_to_string(trust_file)?)?)
+    } else {
+        Ok(Map::new())
+    }
+}
+
+fn is_trusted_in(trust_file: &Path, source: &str, commit: &str) -> io::Result<bool> {
+    Ok(match read_trust_file(trust_file)?.get(source) {
+        Some(Value::Array(commits)) => commits.iter().any(|it| it.as_str() == Some(commit)),
+        _ => false,
+    })
+}
+
+fn store_trust_into(trust_file: &Path, source: &str, commit: &str) -> io::Result<()> {
+    let mut all_trusted = read_trust_file(trust_file)?;
+
+    match all_trusted
+        .entry(source)
+        .or_insert_with(|| Value::Array(vec![]))
+    {
+        Value::Array(commits) => commits.push(Value::String(commit.into())),
+        other => *other = Value::Array(vec![Value::String(commit.into())]),
+    }
+
+    if let Some(parent) = trust_file.parent() {
+        create_dir_all(parent)?;
+    }
+
+    write(trust_file, serde_json::to_string_pretty(&all_trusted)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use tempfile::tempdir;
+
+    use crate::config::read_config;
+    use crate::utils::ToolConfig;
+
+    use super::*;
+
+    #[test]
+    fn test_template_commands() -> io::Result<()> {
+        let config = read_config(
+            r#"{
+    "helpers": [
+        { "name": "percent", "script": "percent.rhai" },
+        { "name": "libVersion", "command": ["./tools/lib-version", "--latest"] }
+    ],
+    "hooks": {
+        "preGenerate": [{ "command": ["./check-jdk.sh"] }],
+        "postGenerate": [{ "command": ["npm", "install"] }]
+    }
+}"#,
+            &ToolConfig {
+                template: None,
+                verbose: false,
+                strict: false,
+                lint: false,
+                on_existing: None,
+                on_conflict: None,
+                no_history: false,
+                no_init: false,
+                dry_run: false,
+                ignore_checks: false,
+            },
+        )?;
+
+        assert_eq!(
+            vec![
+                "Helper 'libVersion': ./tools/lib-version --latest",
+                "Pre-generate hook: ./check-jdk.sh",
+                "Post-generate hook: npm install",
+            ],
+            template_commands(&config)
+        );
+
+        assert!(template_commands(&Config::empty()).is_empty());
+        assert!(ensure_trusted(&Config::empty(), "template", None, false)?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_store_trust() -> io::Result<()> {
+        let data_dir = tempdir()?;
+        let trust_file = data_dir.path().join("nested/trust.json");
+
+        assert!(!is_trusted_in(&trust_file, "template", "abc")?);
+
+        store_trust_into(&trust_file, "template", "abc")?;
+        store_trust_into(&trust_file, "template", "def")?;
+        store_trust_into(&trust_file, "other-template", "123")?;
+
+        assert!(is_trusted_in(&trust_file, "template", "abc")?);
+        assert!(is_trusted_in(&trust_file, "template", "def")?);
+        assert!(!is_trusted_in(&trust_file, "template", "123")?);
+        assert!(!is_trusted_in(&trust_file, "unknown", "abc")?);
+
+        Ok(())
+    }
+}
@@ -38,14 +38,20 @@ pub mod args {
 pub mod flags {
     pub const DRY_RUN: &str = "dry-run";
     pub const DIRTY: &str = "dirty";
+    pub const FRESH: &str = "fresh";
     pub const LOCAL_GIT: &str = "local-git";
     pub const NO_HISTORY: &str = "no-history";
     pub const NO_INIT: &str = "no-init";
     pub const IGNORE_CHECKS: &str = "ignore-checks";
     pub const VERBOSE: &str = "verbose";
+    pub const STRICT: &str = "strict";
+    pub const LINT: &str = "lint";
+    pub const TRUST: &str = "trust";
 }
 
 pub mod options {
     pub const BRANCH: &str = "branch";
+    pub const ON_CONFLICT: &str = "on-conflict";
+    pub const ON_EXISTING: &str = "on-existing";
     pub const TEMPLATE: &str = "template";
 }
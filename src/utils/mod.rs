@@ -30,6 +30,9 @@
  * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
  */
 
+use std::io;
+use std::path::Path;
+
 use clap::ArgMatches;
 use globset::{Error, GlobBuilder, GlobMatcher};
 use lazy_static::lazy_static;
@@ -40,7 +43,7 @@ use crate::{constants::*, TrimmedValueOf};
 pub mod constants;
 pub mod context;
 pub mod errors;
-pub mod reader;
+pub mod text;
 
 pub struct ToolConfig<'tc> {
     pub template: Option<&'tc str>,
@@ -49,6 +52,10 @@ pub struct ToolConfig<'tc> {
     pub ignore_checks: bool,
     pub dry_run: bool,
     pub verbose: bool,
+    pub strict: bool,
+    pub lint: bool,
+    pub on_existing: Option<OnExisting>,
+    pub on_conflict: Option<ConflictStrategy>,
 }
 
 impl<'tc> ToolConfig<'tc> {
@@ -63,6 +70,71 @@ impl<'tc> ToolConfig<'tc> {
             ignore_checks: matches.is_present(flags::IGNORE_CHECKS),
             dry_run: matches.is_present(flags::DRY_RUN),
             verbose: matches.is_present(flags::VERBOSE),
+            strict: matches.is_present(flags::STRICT),
+            lint: matches.is_present(flags::LINT),
+            on_existing: matches
+                .value_of(options::ON_EXISTING)
+                .and_then(OnExisting::parse),
+            on_conflict: matches
+                .value_of(options::ON_CONFLICT)
+                .and_then(ConflictStrategy::parse),
+        }
+    }
+}
+
+/// What happens to files that already exist in the target directory
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OnExisting {
+    Skip,
+    Overwrite,
+    Prompt,
+    Backup,
+}
+
+impl OnExisting {
+    pub const VALUES: [&'static str; 4] = ["skip", "overwrite", "prompt", "backup"];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "skip" => Some(OnExisting::Skip),
+            "overwrite" => Some(OnExisting::Overwrite),
+            "prompt" => Some(OnExisting::Prompt),
+            "backup" => Some(OnExisting::Backup),
+            _ => None,
+        }
+    }
+}
+
+/// What happens if multiple source files are rendered to the same target
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ConflictStrategy {
+    /// Nothing is written and the conflicts are reported
+    Error,
+    FirstWins,
+    LastWins,
+    /// The contents of all sources are written to the target, requires text files
+    Concatenate,
+    /// The source matching the earliest of the priority globs wins
+    Priority,
+}
+
+impl ConflictStrategy {
+    pub const VALUES: [&'static str; 5] = [
+        "error",
+        "first-wins",
+        "last-wins",
+        "concatenate",
+        "priority",
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "error" => Some(ConflictStrategy::Error),
+            "first-wins" => Some(ConflictStrategy::FirstWins),
+            "last-wins" => Some(ConflictStrategy::LastWins),
+            "concatenate" => Some(ConflictStrategy::Concatenate),
+            "priority" => Some(ConflictStrategy::Priority),
+            _ => None,
         }
     }
 }
@@ -76,6 +148,13 @@ pub fn is_identifier(value: &str) -> bool {
     ID_REGEX.is_match(value)
 }
 
+/// Parses Unix permissions specified as an octal number, e.g. `755`
+pub fn parse_mode(value: &str) -> Option<u32> {
+    u32::from_str_radix(value.trim(), 8)
+        .ok()
+        .filter(|&mode| mode <= 0o7777)
+}
+
 pub fn glob(input: &str) -> Result<GlobMatcher, Error> {
     GlobBuilder::new(input)
         .case_insensitive(true)
@@ -84,6 +163,26 @@ pub fn glob(input: &str) -> Result<GlobMatcher, Error> {
         .map(|it| it.compile_matcher())
 }
 
+/// Creates a symbolic link at `path`, `link_target` is relative to the directory of the link
+pub fn symlink(link_target: &Path, path: &Path) -> io::Result<()> {
+    #[cfg(unix)]
+    {
+        std::os::unix::fs::symlink(link_target, path)
+    }
+
+    #[cfg(windows)]
+    {
+        if path
+            .parent()
+            .is_some_and(|parent| parent.join(link_target).is_dir())
+        {
+            std::os::windows::fs::symlink_dir(link_target, path)
+        } else {
+            std::os::windows::fs::symlink_file(link_target, path)
+        }
+    }
+}
+
 #[cfg(test)]
 pub(crate) mod tests {
     use std::path::PathBuf;
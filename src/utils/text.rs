@@ -0,0 +1,183 @@
+/*
+ * BSD 3-Clause License
+ *
+ * Copyright (c) 2021, Alex Katlein
+ * All rights reserved.
+ *
+ * Redistribution and use in source and binary forms, with or without
+ * modification, are permitted provided that the following conditions are met:
+ *
+ * 1. Redistributions of source code must retain the above copyright notice, this
+ *    list of conditions and the following disclaimer.
+ *
+ * 2. Redistributions in binary form must reproduce the above copyright notice,
+ *    this list of conditions and the following disclaimer in the documentation
+ *    and/or other materials provided with the distribution.
+ *
+ * 3. Neither the name of the copyright holder nor the names of its
+ *    contributors may be used to endorse or promote products derived from
+ *    this software without specific prior written permission.
+ *
+ * THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS"
+ * AND ANY EXPRESS OR IMPLIED WARRANTIES, INCLUDING, BUT NOT LIMITED TO, THE
+ * IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+ * DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE
+ * FOR ANY DIRECT, INDIRECT, INCIDENTAL, SPECIAL, EXEMPLARY, OR CONSEQUENTIAL
+ * DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+ * SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER
+ * CAUSED AND ON ANY THEORY OF LIABILITY, WHETHER IN CONTRACT, STRICT LIABILITY,
+ * OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE USE
+ * OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+ */
+
+use std::fs::{read, File};
+use std::io;
+use std::io::{Error, ErrorKind, Read};
+use std::path::Path;
+
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+
+/// The number of bytes at the start of a file that are inspected to detect binary content
+const BINARY_INSPECT_BYTES: u64 = 1024;
+
+/// How the text of a file is encoded, so rendered content can be encoded the same way
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TextEncoding {
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+}
+
+impl TextEncoding {
+    /// The encoding specified by a byte order mark, otherwise the declared encoding or UTF-8
+    pub fn detect(bytes: &[u8], declared: Option<&'static Encoding>) -> Self {
+        match Encoding::for_bom(bytes) {
+            Some((encoding, _)) => TextEncoding {
+                encoding,
+                bom: true,
+            },
+            None => TextEncoding {
+                encoding: declared.unwrap_or(UTF_8),
+                bom: false,
+            },
+        }
+    }
+
+    pub fn decode(&self, bytes: &[u8]) -> io::Result<String> {
+        let bytes = if self.bom {
+            &bytes[self.bom_bytes().len()..]
+        } else {
+            bytes
+        };
+
+        let (content, had_errors) = self.encoding.decode_without_bom_handling(bytes);
+
+        if had_errors {
+            Err(Error::new(
+                ErrorKind::InvalidData,
+                format!("The content isn't valid {}", self.encoding.name()),
+            ))
+        } else {
+            Ok(content.into_owned())
+        }
+    }
+
+    pub fn encode(&self, content: &str) -> Vec<u8> {
+        let mut bytes = if self.bom {
+            self.bom_bytes().to_vec()
+        } else {
+            vec![]
+        };
+
+        // encoding_rs only decodes UTF-16, encoding it yields UTF-8
+        if self.encoding == UTF_16LE {
+            bytes.extend(content.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+        } else if self.encoding == UTF_16BE {
+            bytes.extend(content.encode_utf16().flat_map(|unit| unit.to_be_bytes()));
+        } else {
+            bytes.extend_from_slice(&self.encoding.encode(content).0);
+        }
+
+        bytes
+    }
+
+    fn bom_bytes(&self) -> &'static [u8] {
+        if self.encoding == UTF_16LE {
+            b"\xFF\xFE"
+        } else if self.encoding == UTF_16BE {
+            b"\xFE\xFF"
+        } else {
+            b"\xEF\xBB\xBF"
+        }
+    }
+}
+
+/// Reads the start of the file, which is enough to detect binary content and byte order marks
+pub fn read_head(path: &Path) -> io::Result<Vec<u8>> {
+    let mut head = Vec::new();
+    File::open(path)?
+        .take(BINARY_INSPECT_BYTES)
+        .read_to_end(&mut head)?;
+
+    Ok(head)
+}
+
+/// Whether the start of a file looks like binary content, e.g. images or archives
+pub fn is_binary(head: &[u8]) -> bool {
+    content_inspector::inspect(head).is_binary()
+}
+
+/// Reads and decodes a text file, see [TextEncoding::detect]
+pub fn read_text(
+    path: &Path,
+    declared: Option<&'static Encoding>,
+) -> io::Result<(String, TextEncoding)> {
+    let bytes = read(path)?;
+    let encoding = TextEncoding::detect(&bytes, declared);
+
+    Ok((encoding.decode(&bytes)?, encoding))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::write;
+
+    use encoding_rs::WINDOWS_1252;
+    use tempfile::tempdir;
+
+    use super::*;
+
+    #[test]
+    fn test_text_encoding() -> io::Result<()> {
+        let latin = TextEncoding::detect(b"Gr\xFC\xDFe {{name}}", Some(WINDOWS_1252));
+        assert_eq!(WINDOWS_1252, latin.encoding);
+        assert_eq!("Grüße {{name}}", latin.decode(b"Gr\xFC\xDFe {{name}}")?);
+        assert_eq!(b"Gr\xFC\xDFe".to_vec(), latin.encode("Grüße"));
+
+        let utf16 = TextEncoding::detect(b"\xFF\xFEa\0", Some(WINDOWS_1252));
+        assert_eq!(UTF_16LE, utf16.encoding);
+        assert_eq!("a", utf16.decode(b"\xFF\xFEa\0")?);
+        assert_eq!(b"\xFF\xFEb\0".to_vec(), utf16.encode("b"));
+
+        let utf8 = TextEncoding::detect(b"\xFC", None);
+        assert_eq!(UTF_8, utf8.encoding);
+        assert!(utf8.decode(b"\xFC").is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_binary() -> io::Result<()> {
+        let dir = tempdir()?;
+
+        write(
+            dir.path().join("image.png"),
+            b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR{{",
+        )?;
+        write(dir.path().join("latin.txt"), b"Gr\xFC\xDFe {{name}}")?;
+
+        assert!(is_binary(&read_head(&dir.path().join("image.png"))?));
+        assert!(!is_binary(&read_head(&dir.path().join("latin.txt"))?));
+
+        Ok(())
+    }
+}